@@ -0,0 +1,389 @@
+//! `brm` - headless command-line interface for BetterReplacementsManager
+//!
+//! Every command prints JSON to stdout so it can be scripted from dotfiles and CI.
+//! Errors go to stderr with a non-zero exit code.
+
+use clap::{Parser, Subcommand};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+use std::process::ExitCode;
+
+use brm_core::categories::load_project_categories_data;
+use brm_core::espanso::{
+    lint_espanso_file, list_espanso_yaml_files, read_espanso_file, validate_espanso_file,
+    write_espanso_file, Replacement,
+};
+use brm_core::extensions::load_saved_extensions_data;
+use brm_core::paths::{get_app_data_file_path, get_espanso_file_path};
+use brm_core::projects::{
+    clear_project_espanso_config, create_project, load_project_data, set_active_project, Project,
+};
+use brm_core::render::{load_render_context, render_replacement};
+use brm_core::variables::{load_custom_variables_data, save_custom_variables_data};
+use brm_core::{mcp, server};
+
+#[derive(Parser)]
+#[command(
+    name = "brm",
+    version,
+    about = "Manage BetterReplacementsManager data without the GUI"
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Manage projects
+    #[command(subcommand)]
+    Projects(ProjectsCommand),
+    /// Inspect project categories and their variable definitions
+    #[command(subcommand)]
+    Categories(CategoriesCommand),
+    /// Manage custom variables
+    #[command(subcommand)]
+    Variables(VariablesCommand),
+    /// Inspect saved extensions
+    #[command(subcommand)]
+    Extensions(ExtensionsCommand),
+    /// Read, write and check Espanso match files
+    #[command(subcommand)]
+    Match(MatchCommand),
+    /// Serve the local HTTP/JSON-RPC API on 127.0.0.1
+    Serve {
+        #[arg(long, default_value_t = server::DEFAULT_PORT)]
+        port: u16,
+        /// Generate a new API token, invalidating the old one
+        #[arg(long)]
+        rotate_token: bool,
+    },
+    /// Run a Model Context Protocol server on stdin/stdout for AI coding agents
+    Mcp,
+    /// Activate a project (called by the generated `:project` selector)
+    #[command(name = "select-project", hide = true)]
+    SelectProject { project_id: String },
+}
+
+#[derive(Subcommand)]
+enum ProjectsCommand {
+    /// List all projects and the active project id
+    List,
+    /// Show a single project
+    Show { project: String },
+    /// Create a project
+    Create {
+        #[arg(long)]
+        name: String,
+        #[arg(long)]
+        description: Option<String>,
+        /// Project category id
+        #[arg(long, default_value = "general")]
+        category: String,
+        /// Variable value as category.variable=value (repeatable)
+        #[arg(long = "set", value_name = "CATEGORY.VARIABLE=VALUE")]
+        values: Vec<String>,
+    },
+    /// Make a project active and regenerate its Espanso variables
+    Activate { project: String },
+    /// Clear the active project
+    Deactivate,
+}
+
+#[derive(Subcommand)]
+enum CategoriesCommand {
+    /// List project categories
+    List,
+}
+
+#[derive(Subcommand)]
+enum VariablesCommand {
+    /// List custom variable categories and their variables
+    List,
+    /// Show a custom variable by name
+    Get { name: String },
+    /// Set the value of an existing custom variable
+    Set { name: String, value: String },
+}
+
+#[derive(Subcommand)]
+enum ExtensionsCommand {
+    /// List saved extensions
+    List,
+    /// Show a saved extension by id or name
+    Show { extension: String },
+}
+
+#[derive(Subcommand)]
+enum MatchCommand {
+    /// List the YAML files in the Espanso match directory
+    Files,
+    /// Print the replacements in a match file
+    Read { file: String },
+    /// Replace the contents of a match file with a JSON array of replacements
+    Write {
+        file: String,
+        /// Read the replacements from this file instead of stdin
+        #[arg(long)]
+        input: Option<String>,
+    },
+    /// Report problems in a match file (exits non-zero when any are found)
+    Lint { file: String },
+    /// Check that a match file parses
+    Validate { file: String },
+    /// Render a replacement with the global, project and custom variables
+    Render {
+        file: String,
+        trigger: String,
+        /// Form field value as field=value (repeatable)
+        #[arg(long = "form", value_name = "FIELD=VALUE")]
+        form_values: Vec<String>,
+        /// Chosen value for a choice or random var as var=value (repeatable)
+        #[arg(long = "choice", value_name = "VAR=VALUE")]
+        choices: Vec<String>,
+        /// Variable value overriding every other source as var=value (repeatable)
+        #[arg(long = "set", value_name = "VAR=VALUE")]
+        overrides: Vec<String>,
+    },
+}
+
+fn print_json<T: Serialize>(value: &T) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(value)
+        .map_err(|e| format!("Failed to serialize output: {}", e))?;
+    println!("{}", json);
+    Ok(())
+}
+
+/// Resolve a match file argument: a path is used as-is, a bare name is looked up in the match directory
+fn resolve_match_file(file: &str) -> Result<String, String> {
+    if file.contains('/') || file.contains('\\') || Path::new(file).exists() {
+        return Ok(file.to_string());
+    }
+
+    get_espanso_file_path(file).map(|p| p.display().to_string())
+}
+
+/// Find a project by id or (case-insensitive) name
+fn find_project(key: &str) -> Result<Project, String> {
+    let data = load_project_data()?;
+    data.projects
+        .into_iter()
+        .find(|p| p.id == key || p.name.eq_ignore_ascii_case(key))
+        .ok_or_else(|| format!("Project not found: {}", key))
+}
+
+/// Parse repeated `name=value` arguments
+fn parse_key_values(values: &[String]) -> Result<HashMap<String, String>, String> {
+    values
+        .iter()
+        .map(|entry| {
+            entry
+                .split_once('=')
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .ok_or_else(|| format!("Expected NAME=VALUE, got '{}'", entry))
+        })
+        .collect()
+}
+
+/// Parse repeated `category.variable=value` arguments into category values
+fn parse_category_values(
+    values: &[String],
+) -> Result<HashMap<String, HashMap<String, String>>, String> {
+    let mut category_values: HashMap<String, HashMap<String, String>> = HashMap::new();
+
+    for entry in values {
+        let (key, value) = entry
+            .split_once('=')
+            .ok_or_else(|| format!("Expected CATEGORY.VARIABLE=VALUE, got '{}'", entry))?;
+        let (category, variable) = key
+            .split_once('.')
+            .ok_or_else(|| format!("Expected CATEGORY.VARIABLE=VALUE, got '{}'", entry))?;
+
+        category_values
+            .entry(category.to_string())
+            .or_default()
+            .insert(variable.to_string(), value.to_string());
+    }
+
+    Ok(category_values)
+}
+
+fn run_projects(command: ProjectsCommand) -> Result<(), String> {
+    match command {
+        ProjectsCommand::List => print_json(&load_project_data()?),
+        ProjectsCommand::Show { project } => print_json(&find_project(&project)?),
+        ProjectsCommand::Create {
+            name,
+            description,
+            category,
+            values,
+        } => {
+            let now = chrono::Utc::now().to_rfc3339();
+            let project = Project {
+                id: uuid::Uuid::new_v4().to_string(),
+                name,
+                description,
+                category_id: category,
+                is_active: false,
+                created_at: now.clone(),
+                updated_at: now,
+                category_values: Some(parse_category_values(&values)?),
+            };
+
+            create_project(project.clone())?;
+            print_json(&project)
+        }
+        ProjectsCommand::Activate { project } => {
+            let project = find_project(&project)?;
+            set_active_project(Some(project.id.clone()))?;
+            print_json(&project)
+        }
+        ProjectsCommand::Deactivate => {
+            set_active_project(None)?;
+            clear_project_espanso_config()?;
+            print_json(&serde_json::json!({ "activeProjectId": null }))
+        }
+    }
+}
+
+fn run_variables(command: VariablesCommand) -> Result<(), String> {
+    match command {
+        VariablesCommand::List => print_json(&load_custom_variables_data()?),
+        VariablesCommand::Get { name } => {
+            let data = load_custom_variables_data()?;
+            let variable = data
+                .categories
+                .iter()
+                .flat_map(|c| c.variables.iter())
+                .find(|v| v.name == name)
+                .ok_or_else(|| format!("Custom variable not found: {}", name))?;
+            print_json(variable)
+        }
+        VariablesCommand::Set { name, value } => {
+            let mut data = load_custom_variables_data()?;
+            let variable = data
+                .categories
+                .iter_mut()
+                .flat_map(|c| c.variables.iter_mut())
+                .find(|v| v.name == name)
+                .ok_or_else(|| format!("Custom variable not found: {}", name))?;
+            variable.value = value;
+            let updated = variable.clone();
+
+            data.last_updated = chrono::Utc::now().to_rfc3339();
+            save_custom_variables_data(&data)?;
+            print_json(&updated)
+        }
+    }
+}
+
+fn run_extensions(command: ExtensionsCommand) -> Result<(), String> {
+    let data = load_saved_extensions_data()?;
+    match command {
+        ExtensionsCommand::List => print_json(&data.extensions),
+        ExtensionsCommand::Show { extension } => {
+            let found = data
+                .extensions
+                .iter()
+                .find(|e| e.id == extension || e.name == extension)
+                .ok_or_else(|| format!("Saved extension not found: {}", extension))?;
+            print_json(found)
+        }
+    }
+}
+
+/// Run a match subcommand, returning whether it succeeded without findings
+fn run_match(command: MatchCommand) -> Result<bool, String> {
+    match command {
+        MatchCommand::Files => print_json(&list_espanso_yaml_files()?).map(|_| true),
+        MatchCommand::Read { file } => {
+            print_json(&read_espanso_file(resolve_match_file(&file)?)?).map(|_| true)
+        }
+        MatchCommand::Write { file, input } => {
+            let json = match input {
+                Some(path) => fs::read_to_string(&path)
+                    .map_err(|e| format!("Failed to read {}: {}", path, e))?,
+                None => {
+                    let mut buffer = String::new();
+                    std::io::stdin()
+                        .read_to_string(&mut buffer)
+                        .map_err(|e| format!("Failed to read stdin: {}", e))?;
+                    buffer
+                }
+            };
+            let replacements: Vec<Replacement> = serde_json::from_str(&json)
+                .map_err(|e| format!("Failed to parse replacements JSON: {}", e))?;
+
+            let count = replacements.len();
+            write_espanso_file(resolve_match_file(&file)?, replacements)?;
+            print_json(&serde_json::json!({ "file": file, "written": count })).map(|_| true)
+        }
+        MatchCommand::Lint { file } => {
+            let issues = lint_espanso_file(resolve_match_file(&file)?)?;
+            print_json(&issues)?;
+            Ok(issues.is_empty())
+        }
+        MatchCommand::Validate { file } => {
+            validate_espanso_file(resolve_match_file(&file)?)?;
+            print_json(&serde_json::json!({ "file": file, "valid": true })).map(|_| true)
+        }
+        MatchCommand::Render {
+            file,
+            trigger,
+            form_values,
+            choices,
+            overrides,
+        } => {
+            let replacement = read_espanso_file(resolve_match_file(&file)?)?
+                .into_iter()
+                .find(|r| r.trigger == trigger)
+                .ok_or_else(|| format!("Trigger {} not found in {}", trigger, file))?;
+
+            let mut ctx = load_render_context()?;
+            ctx.form_values = parse_key_values(&form_values)?;
+            ctx.choices = parse_key_values(&choices)?;
+            ctx.overrides = parse_key_values(&overrides)?;
+
+            print_json(&render_replacement(&replacement, &ctx)?).map(|_| true)
+        }
+    }
+}
+
+fn run(cli: Cli) -> Result<bool, String> {
+    match cli.command {
+        Command::Projects(command) => run_projects(command).map(|_| true),
+        Command::Categories(CategoriesCommand::List) => {
+            print_json(&load_project_categories_data()?.categories).map(|_| true)
+        }
+        Command::Variables(command) => run_variables(command).map(|_| true),
+        Command::Extensions(command) => run_extensions(command).map(|_| true),
+        Command::Match(command) => run_match(command),
+        Command::Serve { port, rotate_token } => {
+            let token = server::load_or_create_token(rotate_token)?;
+            eprintln!(
+                "Serving on http://127.0.0.1:{} (token in {})",
+                port,
+                get_app_data_file_path("api_token")?.display()
+            );
+            server::serve(port, token).map(|_| true)
+        }
+        Command::Mcp => mcp::serve_stdio().map(|_| true),
+        Command::SelectProject { project_id } => set_active_project(Some(project_id)).map(|_| true),
+    }
+}
+
+fn main() -> ExitCode {
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("warn")).init();
+
+    match run(Cli::parse()) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
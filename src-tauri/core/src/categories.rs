@@ -0,0 +1,581 @@
+use log::info;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::yaml_utils::atomic_write;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Category {
+    pub id: String,
+    pub name: String,
+    #[serde(rename = "fileName")]
+    pub file_name: String,
+    #[serde(rename = "categoryId")]
+    pub category_id: Option<String>, // Links to ProjectCategory.id
+    pub description: Option<String>,
+    pub icon: String,
+    pub color: Option<String>,
+    #[serde(rename = "isDefault")]
+    pub is_default: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CategoriesData {
+    pub categories: Vec<Category>,
+}
+
+// Project Categories structures
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ProjectCategoryVariable {
+    pub id: String,
+    pub name: String,
+    pub description: Option<String>,
+    #[serde(rename = "defaultValue")]
+    pub default_value: Option<String>,
+    pub required: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ProjectCategory {
+    pub id: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub icon: Option<String>,
+    pub color: Option<String>,
+    #[serde(rename = "isDefault")]
+    pub is_default: Option<bool>,
+    #[serde(rename = "fileName")]
+    pub file_name: Option<String>,
+    #[serde(rename = "variableDefinitions")]
+    pub variable_definitions: Vec<ProjectCategoryVariable>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProjectCategoriesData {
+    pub categories: Vec<ProjectCategory>,
+    #[serde(rename = "lastUpdated")]
+    pub last_updated: String,
+}
+
+// Category management functions
+fn get_categories_file_path() -> Result<PathBuf, String> {
+    use crate::paths::get_espanso_config_dir_internal;
+    let config_dir = get_espanso_config_dir_internal()?;
+    Ok(config_dir.join("config").join("categories.json"))
+}
+
+pub fn load_categories_data() -> Result<CategoriesData, String> {
+    let file_path = get_categories_file_path()?;
+
+    if file_path.exists() {
+        let content = fs::read_to_string(&file_path)
+            .map_err(|e| format!("Failed to read categories file: {}", e))?;
+        serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse categories data: {}", e))
+    } else {
+        // Return default categories if file doesn't exist
+        Ok(CategoriesData {
+            categories: vec![
+                Category {
+                    id: "1".to_string(),
+                    name: "Global".to_string(),
+                    file_name: "better_replacements.yml".to_string(),
+                    category_id: Some("general".to_string()),
+                    description: Some("Global text replacements".to_string()),
+                    icon: "FileTextOutlined".to_string(),
+                    color: None,
+                    is_default: Some(true),
+                },
+                Category {
+                    id: "2".to_string(),
+                    name: "Base".to_string(),
+                    file_name: "base.yml".to_string(),
+                    category_id: Some("general".to_string()),
+                    description: Some("Base replacements and snippets".to_string()),
+                    icon: "CodeOutlined".to_string(),
+                    color: None,
+                    is_default: Some(true),
+                },
+                Category {
+                    id: "3".to_string(),
+                    name: "AI Prompts".to_string(),
+                    file_name: "ai_prompts.yml".to_string(),
+                    category_id: Some("development".to_string()),
+                    description: Some("AI-related prompts and templates".to_string()),
+                    icon: "RobotOutlined".to_string(),
+                    color: None,
+                    is_default: Some(true),
+                },
+            ],
+        })
+    }
+}
+
+pub fn save_categories_data(data: &CategoriesData) -> Result<(), String> {
+    let file_path = get_categories_file_path()?;
+
+    // Ensure directory exists
+    if let Some(parent) = file_path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create categories directory: {}", e))?;
+    }
+
+    let json = serde_json::to_string_pretty(data)
+        .map_err(|e| format!("Failed to serialize categories data: {}", e))?;
+    fs::write(&file_path, json).map_err(|e| format!("Failed to write categories file: {}", e))?;
+
+    Ok(())
+}
+
+pub fn get_categories() -> Result<Vec<Category>, String> {
+    let data = load_categories_data()?;
+    Ok(data.categories)
+}
+
+pub fn create_category(category: Category) -> Result<(), String> {
+    let mut data = load_categories_data()?;
+
+    // Check if file name already exists
+    if data
+        .categories
+        .iter()
+        .any(|c| c.file_name == category.file_name)
+    {
+        return Err("A category with this file name already exists".to_string());
+    }
+
+    // Create the YAML file for the new category
+    use crate::paths::get_espanso_file_path;
+    let yaml_path = get_espanso_file_path(&category.file_name)?;
+
+    if !yaml_path.exists() {
+        let initial_content = "matches:\n  # Add your replacements here\n";
+        fs::write(&yaml_path, initial_content)
+            .map_err(|e| format!("Failed to create category file: {}", e))?;
+    }
+
+    data.categories.push(category);
+    save_categories_data(&data)
+}
+
+pub fn update_category(id: String, updates: Value) -> Result<(), String> {
+    let mut data = load_categories_data()?;
+
+    if let Some(category) = data.categories.iter_mut().find(|c| c.id == id) {
+        // Don't allow updating default categories
+        if category.is_default.unwrap_or(false) {
+            return Err("Cannot update default categories".to_string());
+        }
+
+        if let Some(obj) = updates.as_object() {
+            if let Some(name) = obj.get("name").and_then(|v| v.as_str()) {
+                category.name = name.to_string();
+            }
+            if let Some(desc) = obj.get("description").and_then(|v| v.as_str()) {
+                category.description = Some(desc.to_string());
+            }
+            if let Some(icon) = obj.get("icon").and_then(|v| v.as_str()) {
+                category.icon = icon.to_string();
+            }
+            if let Some(color) = obj.get("color").and_then(|v| v.as_str()) {
+                category.color = Some(color.to_string());
+            }
+        }
+        save_categories_data(&data)
+    } else {
+        Err("Category not found".to_string())
+    }
+}
+
+pub fn delete_category(id: String) -> Result<(), String> {
+    let mut data = load_categories_data()?;
+
+    // Find the category to delete
+    if let Some(category) = data.categories.iter().find(|c| c.id == id) {
+        // Don't allow deleting default categories
+        if category.is_default.unwrap_or(false) {
+            return Err("Cannot delete default categories".to_string());
+        }
+
+        // Delete the YAML file
+        use crate::paths::get_espanso_file_path;
+        let yaml_path = get_espanso_file_path(&category.file_name)?;
+
+        if yaml_path.exists() {
+            fs::remove_file(&yaml_path)
+                .map_err(|e| format!("Failed to delete category file: {}", e))?;
+        }
+    }
+
+    data.categories.retain(|c| c.id != id);
+    save_categories_data(&data)
+}
+
+// Project Categories management functions
+fn get_project_categories_file_path() -> Result<PathBuf, String> {
+    use crate::paths::get_espanso_config_dir_internal;
+    let espanso_config = get_espanso_config_dir_internal()?;
+    let config_subdir = espanso_config.join("config");
+
+    // Ensure config subdirectory exists
+    if !config_subdir.exists() {
+        fs::create_dir_all(&config_subdir)
+            .map_err(|e| format!("Failed to create espanso/config directory: {}", e))?;
+    }
+
+    Ok(config_subdir.join("project_categories.json"))
+}
+
+pub fn load_project_categories_data() -> Result<ProjectCategoriesData, String> {
+    let file_path = get_project_categories_file_path()?;
+
+    if file_path.exists() {
+        let content = fs::read_to_string(&file_path)
+            .map_err(|e| format!("Failed to read project categories file: {}", e))?;
+        serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse project categories data: {}", e))
+    } else {
+        // Return default categories if file doesn't exist
+        let default_categories = vec![
+            ProjectCategory {
+                id: "general".to_string(),
+                name: "General".to_string(),
+                description: Some("Basic project information".to_string()),
+                icon: Some("InfoCircleOutlined".to_string()),
+                color: Some("#1890ff".to_string()),
+                is_default: Some(true),
+                file_name: Some("project_general.yml".to_string()),
+                variable_definitions: vec![
+                    ProjectCategoryVariable {
+                        id: "project_name".to_string(),
+                        name: "project_name".to_string(),
+                        description: Some("The name of your project".to_string()),
+                        default_value: None,
+                        required: Some(false),
+                    },
+                    ProjectCategoryVariable {
+                        id: "active_project_name".to_string(),
+                        name: "active_project_name".to_string(),
+                        description: Some(
+                            "The name of the active project (legacy compatibility)".to_string(),
+                        ),
+                        default_value: None,
+                        required: Some(false),
+                    },
+                    ProjectCategoryVariable {
+                        id: "project_description".to_string(),
+                        name: "project_description".to_string(),
+                        description: Some("A brief description of the project".to_string()),
+                        default_value: None,
+                        required: Some(false),
+                    },
+                ],
+            },
+            ProjectCategory {
+                id: "development".to_string(),
+                name: "Development".to_string(),
+                description: Some("Development-related variables".to_string()),
+                icon: Some("CodeOutlined".to_string()),
+                color: Some("#52c41a".to_string()),
+                is_default: Some(true),
+                file_name: Some("project_development.yml".to_string()),
+                variable_definitions: vec![
+                    ProjectCategoryVariable {
+                        id: "tech_stack".to_string(),
+                        name: "tech_stack".to_string(),
+                        description: Some("Technology stack used".to_string()),
+                        default_value: Some("TypeScript".to_string()),
+                        required: Some(false),
+                    },
+                    ProjectCategoryVariable {
+                        id: "active_project_stack".to_string(),
+                        name: "active_project_stack".to_string(),
+                        description: Some("Technology stack (legacy compatibility)".to_string()),
+                        default_value: Some("TypeScript".to_string()),
+                        required: Some(false),
+                    },
+                    ProjectCategoryVariable {
+                        id: "directory".to_string(),
+                        name: "directory".to_string(),
+                        description: Some("Project directory path".to_string()),
+                        default_value: None,
+                        required: Some(false),
+                    },
+                    ProjectCategoryVariable {
+                        id: "active_project_directory".to_string(),
+                        name: "active_project_directory".to_string(),
+                        description: Some("Project directory (legacy compatibility)".to_string()),
+                        default_value: None,
+                        required: Some(false),
+                    },
+                    ProjectCategoryVariable {
+                        id: "restart_command".to_string(),
+                        name: "restart_command".to_string(),
+                        description: Some("Command to restart the project".to_string()),
+                        default_value: Some("npm run dev".to_string()),
+                        required: Some(false),
+                    },
+                    ProjectCategoryVariable {
+                        id: "active_project_restart_cmd".to_string(),
+                        name: "active_project_restart_cmd".to_string(),
+                        description: Some("Restart command (legacy compatibility)".to_string()),
+                        default_value: Some("npm run dev".to_string()),
+                        required: Some(false),
+                    },
+                    ProjectCategoryVariable {
+                        id: "log_command".to_string(),
+                        name: "log_command".to_string(),
+                        description: Some("Command to view logs".to_string()),
+                        default_value: Some("npm run logs".to_string()),
+                        required: Some(false),
+                    },
+                    ProjectCategoryVariable {
+                        id: "active_project_log_cmd".to_string(),
+                        name: "active_project_log_cmd".to_string(),
+                        description: Some("Log command (legacy compatibility)".to_string()),
+                        default_value: Some("npm run logs".to_string()),
+                        required: Some(false),
+                    },
+                ],
+            },
+        ];
+
+        Ok(ProjectCategoriesData {
+            categories: default_categories,
+            last_updated: chrono::Utc::now().to_rfc3339(),
+        })
+    }
+}
+
+pub fn save_project_categories_data(data: &ProjectCategoriesData) -> Result<(), String> {
+    let file_path = get_project_categories_file_path()?;
+
+    // Ensure directory exists
+    if let Some(parent) = file_path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create project categories directory: {}", e))?;
+    }
+
+    let json = serde_json::to_string_pretty(data)
+        .map_err(|e| format!("Failed to serialize project categories data: {}", e))?;
+
+    fs::write(&file_path, json)
+        .map_err(|e| format!("Failed to write project categories file: {}", e))?;
+
+    Ok(())
+}
+
+pub fn write_project_categories(data: ProjectCategoriesData) -> Result<(), String> {
+    // Load existing data to compare for new/updated categories
+    let existing_data = load_project_categories_data().unwrap_or_else(|_| ProjectCategoriesData {
+        categories: vec![],
+        last_updated: chrono::Utc::now().to_rfc3339(),
+    });
+
+    use crate::paths::get_espanso_match_dir_internal;
+    let espanso_match_dir = get_espanso_match_dir_internal()?;
+
+    // Create/update YAML files for all categories
+    for category in &data.categories {
+        if let Some(file_name) = &category.file_name {
+            let yaml_path = espanso_match_dir.join(file_name);
+
+            // Check if this is a new category or if it's being updated
+            let existing_category = existing_data
+                .categories
+                .iter()
+                .find(|c| c.id == category.id);
+            let should_create_file = existing_category.is_none() || !yaml_path.exists();
+
+            if should_create_file {
+                // Create initial YAML content with category description
+                let mut yaml_content = format!("# {}\n", category.name);
+                if let Some(desc) = &category.description {
+                    yaml_content.push_str(&format!("# {}\n", desc));
+                }
+                yaml_content.push_str("matches:\n  # Add your replacements here\n");
+
+                atomic_write(&yaml_path, &yaml_content).map_err(|e| {
+                    format!(
+                        "Failed to create YAML file for category '{}': {}",
+                        category.name, e
+                    )
+                })?;
+
+                info!(
+                    "Created YAML file for category '{}': {:?}",
+                    category.name, yaml_path
+                );
+            }
+        }
+    }
+
+    // Remove YAML files for deleted categories
+    for existing_category in &existing_data.categories {
+        if !data.categories.iter().any(|c| c.id == existing_category.id) {
+            if let Some(file_name) = &existing_category.file_name {
+                let yaml_path = espanso_match_dir.join(file_name);
+                if yaml_path.exists() {
+                    fs::remove_file(&yaml_path).map_err(|e| {
+                        format!(
+                            "Failed to delete YAML file for category '{}': {}",
+                            existing_category.name, e
+                        )
+                    })?;
+                    info!(
+                        "Deleted YAML file for category '{}': {:?}",
+                        existing_category.name, yaml_path
+                    );
+                }
+            }
+        }
+    }
+
+    save_project_categories_data(&data)
+}
+
+pub fn migrate_replacement_categories_to_project_categories() -> Result<(), String> {
+    info!("Starting migration from replacement categories to project categories");
+
+    // Load existing replacement categories
+    let existing_categories =
+        load_categories_data().unwrap_or_else(|_| CategoriesData { categories: vec![] });
+
+    // Load existing project categories
+    let mut project_categories_data = load_project_categories_data()?;
+
+    let mut migrated_count = 0;
+
+    // Migrate each replacement category that doesn't already exist in project categories
+    for old_category in existing_categories.categories {
+        // Skip default categories (they should already exist in project categories)
+        if old_category.is_default.unwrap_or(false) {
+            continue;
+        }
+
+        // Check if this category already exists in project categories
+        let exists = project_categories_data.categories.iter().any(|pc| {
+            pc.file_name.as_ref() == Some(&old_category.file_name) || pc.name == old_category.name
+        });
+
+        if !exists {
+            // Create new project category from replacement category
+            let new_project_category = ProjectCategory {
+                id: format!("migrated_{}", old_category.id),
+                name: old_category.name.clone(),
+                description: old_category.description.clone(),
+                icon: Some(old_category.icon.clone()),
+                color: old_category.color.clone(),
+                is_default: Some(false),
+                file_name: Some(old_category.file_name.clone()),
+                variable_definitions: vec![], // Start with empty variable definitions
+            };
+
+            project_categories_data
+                .categories
+                .push(new_project_category);
+            migrated_count += 1;
+
+            info!(
+                "Migrated category '{}' with file '{}'",
+                old_category.name, old_category.file_name
+            );
+        }
+    }
+
+    // Save updated project categories
+    if migrated_count > 0 {
+        project_categories_data.last_updated = chrono::Utc::now().to_rfc3339();
+        save_project_categories_data(&project_categories_data)?;
+        info!(
+            "Migration completed: {} categories migrated",
+            migrated_count
+        );
+    } else {
+        info!("Migration completed: no new categories to migrate");
+    }
+
+    Ok(())
+}
+
+pub fn ensure_project_categories_have_filenames() -> Result<(), String> {
+    info!("Ensuring all project categories have fileName fields and YAML files");
+
+    let mut data = load_project_categories_data()?;
+    let mut updated = false;
+
+    use crate::paths::get_espanso_match_dir_internal;
+    let espanso_match_dir = get_espanso_match_dir_internal()?;
+
+    for category in &mut data.categories {
+        let mut category_updated = false;
+
+        // Add fileName if missing
+        if category.file_name.is_none() {
+            // Generate fileName from category name or use fallback based on id
+            let file_name = if category.name.is_empty() {
+                format!("{}.yml", category.id)
+            } else {
+                format!(
+                    "{}.yml",
+                    category
+                        .name
+                        .to_lowercase()
+                        .replace(' ', "_")
+                        .replace("-", "_")
+                )
+            };
+
+            category.file_name = Some(file_name.clone());
+            category_updated = true;
+
+            info!(
+                "Added fileName '{}' to category '{}'",
+                file_name, category.name
+            );
+        }
+
+        // Create YAML file if it doesn't exist
+        if let Some(file_name) = &category.file_name {
+            let yaml_path = espanso_match_dir.join(file_name);
+
+            if !yaml_path.exists() {
+                // Create initial YAML content with category description
+                let mut yaml_content = format!("# {}\n", category.name);
+                if let Some(desc) = &category.description {
+                    yaml_content.push_str(&format!("# {}\n", desc));
+                }
+                yaml_content.push_str("matches:\n  # Add your replacements here\n  # Example:\n  # - trigger: \":hello\"\n  #   replace: \"Hello, World!\"\n");
+
+                atomic_write(&yaml_path, &yaml_content).map_err(|e| {
+                    format!(
+                        "Failed to create YAML file for category '{}': {}",
+                        category.name, e
+                    )
+                })?;
+
+                info!(
+                    "Created YAML file for category '{}': {:?}",
+                    category.name, yaml_path
+                );
+                category_updated = true;
+            }
+        }
+
+        if category_updated {
+            updated = true;
+        }
+    }
+
+    if updated {
+        data.last_updated = chrono::Utc::now().to_rfc3339();
+        save_project_categories_data(&data)?;
+        info!("Updated project categories with fileName fields and created missing YAML files");
+    } else {
+        info!("All project categories already have fileName fields and YAML files");
+    }
+
+    Ok(())
+}
@@ -0,0 +1,418 @@
+use log::{error, info};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EspansoMatch {
+    pub trigger: String,
+    pub replace: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vars: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub case_sensitive: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub word_boundary: Option<bool>,
+    #[serde(flatten)]
+    #[serde(default)]
+    pub extra: HashMap<String, Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EspansoConfig {
+    pub matches: Vec<EspansoMatch>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub global_vars: Option<Value>,
+    #[serde(flatten)]
+    #[serde(default)]
+    pub extra: HashMap<String, Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Replacement {
+    pub trigger: String,
+    pub replace: String,
+    pub source: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vars: Option<Value>,
+    #[serde(default)]
+    pub metadata: HashMap<String, Value>,
+}
+
+/// Parse the contents of an Espanso match file
+fn parse_espanso_config(contents: &str) -> Result<EspansoConfig, String> {
+    serde_yaml::from_str(contents).map_err(|e| format!("Failed to parse YAML: {}", e))
+}
+
+pub fn read_espanso_file(file_path: String) -> Result<Vec<Replacement>, String> {
+    info!("Reading Espanso file: {}", file_path);
+    let path = Path::new(&file_path);
+
+    if !path.exists() {
+        error!("File not found: {}", file_path);
+        return Err(format!("File not found: {}", file_path));
+    }
+
+    let contents = fs::read_to_string(path).map_err(|e| format!("Failed to read file: {}", e))?;
+
+    let config = parse_espanso_config(&contents)?;
+
+    let file_name = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("unknown");
+
+    let replacements = config
+        .matches
+        .into_iter()
+        .map(
+            |EspansoMatch {
+                 trigger,
+                 replace,
+                 vars,
+                 enabled,
+                 case_sensitive,
+                 word_boundary,
+                 extra,
+             }| {
+                let mut metadata = extra;
+                if let Some(value) = enabled {
+                    metadata.insert("enabled".to_string(), Value::Bool(value));
+                }
+                if let Some(value) = case_sensitive {
+                    metadata.insert("case_sensitive".to_string(), Value::Bool(value));
+                }
+                if let Some(value) = word_boundary {
+                    metadata.insert("word_boundary".to_string(), Value::Bool(value));
+                }
+
+                Replacement {
+                    trigger,
+                    replace,
+                    source: file_name.to_string(),
+                    vars,
+                    metadata,
+                }
+            },
+        )
+        .collect();
+
+    Ok(replacements)
+}
+
+pub fn write_espanso_file(file_path: String, replacements: Vec<Replacement>) -> Result<(), String> {
+    info!(
+        "Writing {} replacements to Espanso file: {}",
+        replacements.len(),
+        file_path
+    );
+    let path = Path::new(&file_path);
+
+    // Convert replacements to EspansoConfig
+    let matches: Vec<EspansoMatch> = replacements
+        .into_iter()
+        .map(|r| {
+            let mut metadata = r.metadata;
+            let enabled = metadata.remove("enabled").and_then(|value| value.as_bool());
+            let case_sensitive = metadata
+                .remove("case_sensitive")
+                .and_then(|value| value.as_bool());
+            let word_boundary = metadata
+                .remove("word_boundary")
+                .and_then(|value| value.as_bool());
+
+            EspansoMatch {
+                trigger: r.trigger,
+                replace: r.replace,
+                vars: r.vars,
+                enabled,
+                case_sensitive,
+                word_boundary,
+                extra: metadata,
+            }
+        })
+        .collect();
+
+    let config = EspansoConfig {
+        matches,
+        global_vars: None,
+        extra: HashMap::new(),
+    };
+
+    // Serialize to YAML
+    let yaml_string =
+        serde_yaml::to_string(&config).map_err(|e| format!("Failed to serialize YAML: {}", e))?;
+
+    // Write to file
+    fs::write(path, yaml_string).map_err(|e| format!("Failed to write file: {}", e))?;
+
+    Ok(())
+}
+
+pub fn list_espanso_yaml_files() -> Result<Vec<String>, String> {
+    use crate::paths::get_espanso_match_dir_internal;
+    let espanso_match_dir = get_espanso_match_dir_internal()?;
+
+    if !espanso_match_dir.exists() {
+        return Ok(vec![]);
+    }
+
+    let mut yaml_files = vec![];
+
+    // Read directory and filter for .yml files
+    let entries = fs::read_dir(&espanso_match_dir)
+        .map_err(|e| format!("Failed to read Espanso match directory: {}", e))?;
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_file() {
+            if let Some(extension) = path.extension() {
+                if extension == "yml" || extension == "yaml" {
+                    if let Some(file_name) = path.file_name() {
+                        if let Some(file_name_str) = file_name.to_str() {
+                            yaml_files.push(file_name_str.to_string());
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    yaml_files.sort();
+    Ok(yaml_files)
+}
+
+pub fn delete_espanso_yaml_file(file_name: String) -> Result<(), String> {
+    use crate::paths::get_espanso_file_path;
+    let file_path = get_espanso_file_path(&file_name)?;
+
+    if !file_path.exists() {
+        return Err(format!("File {} not found", file_name));
+    }
+
+    // Don't allow deleting certain protected files
+    let protected_files = [
+        "project_active_vars.yml",
+        "project_global_vars.yml",
+        "project_selector.yml",
+        "categories.json",
+    ];
+
+    if protected_files.contains(&file_name.as_str()) {
+        return Err(format!("Cannot delete protected file: {}", file_name));
+    }
+
+    fs::remove_file(&file_path).map_err(|e| format!("Failed to delete file: {}", e))?;
+
+    info!("Deleted Espanso YAML file: {}", file_name);
+    Ok(())
+}
+
+// ========== Validation ==========
+
+/// Extract the variable names referenced as `{{name}}` in a replacement
+///
+/// Filters (`{{name|upper}}`) and parameters (`{{date:+1d}}`) are stripped, and each
+/// name is only reported once.
+pub fn extract_var_refs(text: &str) -> Vec<String> {
+    let mut refs: Vec<String> = vec![];
+    let mut rest = text;
+
+    while let Some(start) = rest.find("{{") {
+        let after = &rest[start + 2..];
+        let Some(end) = after.find("}}") else {
+            break;
+        };
+
+        let name = after[..end]
+            .split(['|', ':'])
+            .next()
+            .unwrap_or_default()
+            .trim();
+        if !name.is_empty() && !refs.iter().any(|r| r == name) {
+            refs.push(name.to_string());
+        }
+
+        rest = &after[end + 2..];
+    }
+
+    refs
+}
+
+/// Collect the names of the vars declared in a `vars`/`global_vars` list
+fn declared_var_names(vars: Option<&Value>) -> Vec<String> {
+    vars.and_then(|v| v.as_array())
+        .map(|list| {
+            list.iter()
+                .filter_map(|var| var.get("name").and_then(|n| n.as_str()))
+                .map(|n| n.to_string())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Check that a match file parses as an Espanso match file
+pub fn validate_espanso_file(file_path: String) -> Result<(), String> {
+    let contents =
+        fs::read_to_string(&file_path).map_err(|e| format!("Failed to read file: {}", e))?;
+    parse_espanso_config(&contents).map(|_| ())
+}
+
+/// Lint a parsed match file for problems Espanso accepts but that break expansions
+///
+/// `external_globals` are the `global_vars` declared by other match files, which
+/// Espanso makes available to every match.
+pub fn lint_espanso_config(config: &EspansoConfig, external_globals: &[String]) -> Vec<String> {
+    let mut issues = vec![];
+    let mut seen_triggers: Vec<&str> = vec![];
+
+    let mut globals = declared_var_names(config.global_vars.as_ref());
+    globals.extend(external_globals.iter().cloned());
+
+    for m in &config.matches {
+        if m.trigger.trim().is_empty() {
+            issues.push("Match with an empty trigger".to_string());
+            continue;
+        }
+
+        if seen_triggers.contains(&m.trigger.as_str()) {
+            issues.push(format!("{}: duplicate trigger", m.trigger));
+        } else {
+            seen_triggers.push(&m.trigger);
+        }
+
+        if m.replace.is_empty() {
+            issues.push(format!("{}: empty replacement", m.trigger));
+        }
+
+        let locals = declared_var_names(m.vars.as_ref());
+        let refs = extract_var_refs(&m.replace);
+
+        for name in &refs {
+            if !name.starts_with("form.") && !locals.contains(name) && !globals.contains(name) {
+                issues.push(format!("{}: undefined variable '{}'", m.trigger, name));
+            }
+        }
+
+        for name in &locals {
+            if !refs.contains(name) {
+                issues.push(format!("{}: variable '{}' is never used", m.trigger, name));
+            }
+        }
+    }
+
+    issues
+}
+
+/// Lint a match file, resolving global vars against the other files in its directory
+pub fn lint_espanso_file(file_path: String) -> Result<Vec<String>, String> {
+    let path = Path::new(&file_path);
+    let contents = fs::read_to_string(path).map_err(|e| format!("Failed to read file: {}", e))?;
+    let config = parse_espanso_config(&contents)?;
+
+    let mut external_globals = vec![];
+    if let Some(dir) = path.parent() {
+        for entry in fs::read_dir(dir).into_iter().flatten().flatten() {
+            let sibling = entry.path();
+            let is_yaml = sibling
+                .extension()
+                .map(|ext| ext == "yml" || ext == "yaml")
+                .unwrap_or(false);
+            if !is_yaml || sibling == path {
+                continue;
+            }
+
+            let Ok(sibling_contents) = fs::read_to_string(&sibling) else {
+                continue;
+            };
+            if let Ok(value) = serde_yaml::from_str::<Value>(&sibling_contents) {
+                external_globals.extend(declared_var_names(value.get("global_vars")));
+            }
+        }
+    }
+
+    Ok(lint_espanso_config(&config, &external_globals))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_preserve_vars() {
+        let yaml = r#"matches:
+  - trigger: ":test"
+    replace: "value"
+    vars:
+      - name: var1
+        type: echo
+        params:
+          echo: "hello"
+"#;
+        let config: EspansoConfig = serde_yaml::from_str(yaml).unwrap();
+        assert!(config.matches[0].vars.is_some());
+
+        let output = serde_yaml::to_string(&config).unwrap();
+        assert!(output.contains("vars:"));
+        assert!(output.contains("var1"));
+    }
+
+    #[test]
+    fn test_preserve_enabled_flag() {
+        let yaml = r#"matches:
+  - trigger: ":test"
+    replace: "value"
+    enabled: false
+"#;
+        let config: EspansoConfig = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(config.matches[0].enabled, Some(false));
+
+        let output = serde_yaml::to_string(&config).unwrap();
+        assert!(output.contains("enabled: false"));
+    }
+
+    #[test]
+    fn test_extract_var_refs() {
+        let refs = extract_var_refs(
+            "cd {{directory}} && {{restart_command|trim}} {{date:%Y}} {{directory}} {{form.name}}",
+        );
+        assert_eq!(
+            refs,
+            vec!["directory", "restart_command", "date", "form.name"]
+        );
+
+        assert!(extract_var_refs("no vars {{unterminated").is_empty());
+    }
+
+    #[test]
+    fn test_lint_espanso_config() {
+        let yaml = r#"matches:
+  - trigger: ":cd"
+    replace: "cd {{directory}} && {{cmd}}"
+    vars:
+      - name: cmd
+        type: echo
+        params:
+          echo: "ls"
+      - name: unused
+        type: echo
+        params:
+          echo: "x"
+  - trigger: ":cd"
+    replace: ""
+"#;
+        let config = parse_espanso_config(yaml).unwrap();
+
+        let issues = lint_espanso_config(&config, &[]);
+        assert_eq!(issues.len(), 4);
+        assert!(issues.contains(&":cd: undefined variable 'directory'".to_string()));
+        assert!(issues.contains(&":cd: duplicate trigger".to_string()));
+
+        let issues = lint_espanso_config(&config, &["directory".to_string()]);
+        assert_eq!(issues.len(), 3);
+    }
+}
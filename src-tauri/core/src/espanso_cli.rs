@@ -0,0 +1,291 @@
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use serde_yaml::Value as YamlValue;
+use std::collections::HashMap;
+use std::fs;
+use std::process::Command;
+
+use crate::espanso::extract_var_refs;
+use crate::paths::{get_espanso_command_name, get_espanso_match_dir_internal};
+
+/// File generated by `update_espanso_project_vars` with the active project's variables
+const PROJECT_VARS_FILE: &str = "project_active_vars.yml";
+
+/// A match as reported by `espanso match list --json`
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct EspansoLiveMatch {
+    pub triggers: Vec<String>,
+    pub replace: String,
+    #[serde(default)]
+    pub label: Option<String>,
+}
+
+/// Optional app context used to filter `espanso match list` (mirrors its `--class`, `--exec` and `--title` flags)
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct EspansoMatchFilter {
+    pub class: Option<String>,
+    pub exec: Option<String>,
+    pub title: Option<String>,
+}
+
+/// Result of checking a trigger against both Espanso and the match files on disk
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TriggerResolution {
+    pub trigger: String,
+    /// True when Espanso itself reports the trigger as loaded
+    #[serde(rename = "isLive")]
+    pub is_live: bool,
+    /// Matches Espanso reports for this trigger
+    #[serde(rename = "liveMatches")]
+    pub live_matches: Vec<EspansoLiveMatch>,
+    /// Match files in the match directory that declare this trigger
+    #[serde(rename = "sourceFiles")]
+    pub source_files: Vec<String>,
+    /// Variables referenced by the replacement that resolve from project_active_vars.yml
+    #[serde(rename = "projectVars")]
+    pub project_vars: HashMap<String, String>,
+    /// Variables referenced by the replacement that are not defined anywhere Espanso can see
+    #[serde(rename = "unresolvedVars")]
+    pub unresolved_vars: Vec<String>,
+}
+
+// ========== CLI Invocation ==========
+
+/// Run an Espanso CLI command and return its stdout
+fn run_espanso(args: &[&str]) -> Result<String, String> {
+    let output = Command::new(get_espanso_command_name())
+        .args(args)
+        .output()
+        .map_err(|e| {
+            format!(
+                "Failed to execute espanso command: {}. Is Espanso installed?",
+                e
+            )
+        })?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!(
+            "Espanso command 'espanso {}' failed: {}",
+            args.join(" "),
+            stderr.trim()
+        ));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Parse the JSON printed by `espanso match list --json`
+fn parse_match_list(output: &str) -> Result<Vec<EspansoLiveMatch>, String> {
+    serde_json::from_str(output.trim())
+        .map_err(|e| format!("Failed to parse espanso match list output: {}", e))
+}
+
+/// List the matches Espanso has loaded, optionally for a specific app context
+pub fn list_live_matches(filter: &EspansoMatchFilter) -> Result<Vec<EspansoLiveMatch>, String> {
+    let mut args = vec!["match", "list", "--json"];
+    if let Some(class) = &filter.class {
+        args.extend(["--class", class.as_str()]);
+    }
+    if let Some(exec) = &filter.exec {
+        args.extend(["--exec", exec.as_str()]);
+    }
+    if let Some(title) = &filter.title {
+        args.extend(["--title", title.as_str()]);
+    }
+
+    let output = run_espanso(&args)?;
+    parse_match_list(&output)
+}
+
+/// Ask Espanso to expand a trigger into the currently focused application
+pub fn exec_trigger(trigger: &str) -> Result<(), String> {
+    info!("Executing Espanso trigger: {}", trigger);
+    run_espanso(&["match", "exec", "-t", trigger])?;
+    Ok(())
+}
+
+// ========== Match File Inspection ==========
+
+/// Collect the names of the vars declared in a YAML `vars`/`global_vars` list
+fn var_names(vars: Option<&YamlValue>) -> Vec<String> {
+    vars.and_then(|v| v.as_sequence())
+        .map(|seq| {
+            seq.iter()
+                .filter_map(|var| var.get("name").and_then(|n| n.as_str()))
+                .map(|n| n.to_string())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Check whether a match entry declares the given trigger (via `trigger` or `triggers`)
+fn match_has_trigger(entry: &YamlValue, trigger: &str) -> bool {
+    if entry.get("trigger").and_then(|t| t.as_str()) == Some(trigger) {
+        return true;
+    }
+
+    entry
+        .get("triggers")
+        .and_then(|t| t.as_sequence())
+        .map(|seq| seq.iter().any(|t| t.as_str() == Some(trigger)))
+        .unwrap_or(false)
+}
+
+/// Read the echo values of the active project's variables from project_active_vars.yml
+fn read_project_var_values(config: &YamlValue) -> HashMap<String, String> {
+    let mut values = HashMap::new();
+
+    if let Some(vars) = config.get("global_vars").and_then(|v| v.as_sequence()) {
+        for var in vars {
+            let name = var.get("name").and_then(|n| n.as_str());
+            let echo = var
+                .get("params")
+                .and_then(|p| p.get("echo"))
+                .and_then(|e| e.as_str());
+            if let (Some(name), Some(echo)) = (name, echo) {
+                values.insert(name.to_string(), echo.to_string());
+            }
+        }
+    }
+
+    values
+}
+
+/// Resolve a trigger against Espanso and the match directory
+///
+/// Combines what Espanso reports as loaded with the match files that declare the
+/// trigger, so a snippet saved via `write_espanso_file` can be confirmed as live. Files
+/// prefixed with `_` are skipped because Espanso only loads them through `imports`.
+pub fn resolve_trigger(trigger: &str) -> Result<TriggerResolution, String> {
+    let live_matches: Vec<EspansoLiveMatch> = match list_live_matches(&EspansoMatchFilter::default())
+    {
+        Ok(matches) => matches
+            .into_iter()
+            .filter(|m| m.triggers.iter().any(|t| t == trigger))
+            .collect(),
+        Err(e) => {
+            warn!("Could not list Espanso matches: {}", e);
+            vec![]
+        }
+    };
+
+    let match_dir = get_espanso_match_dir_internal()?;
+    let entries = fs::read_dir(&match_dir)
+        .map_err(|e| format!("Failed to read Espanso match directory: {}", e))?;
+
+    let mut source_files = vec![];
+    let mut global_var_names: Vec<String> = vec![];
+    let mut local_var_names: Vec<String> = vec![];
+    let mut replace_texts: Vec<String> = vec![];
+    let mut project_var_values = HashMap::new();
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let Some(file_name) = path.file_name().and_then(|n| n.to_str()) else {
+            continue;
+        };
+        let is_yaml = path
+            .extension()
+            .map(|ext| ext == "yml" || ext == "yaml")
+            .unwrap_or(false);
+        if !path.is_file() || !is_yaml || file_name.starts_with('_') {
+            continue;
+        }
+
+        let Ok(contents) = fs::read_to_string(&path) else {
+            continue;
+        };
+        let config: YamlValue = match serde_yaml::from_str(&contents) {
+            Ok(config) => config,
+            Err(e) => {
+                warn!("Skipping unparsable match file {}: {}", file_name, e);
+                continue;
+            }
+        };
+
+        global_var_names.extend(var_names(config.get("global_vars")));
+        if file_name == PROJECT_VARS_FILE {
+            project_var_values = read_project_var_values(&config);
+        }
+
+        let matches = config.get("matches").and_then(|m| m.as_sequence());
+        for entry in matches.into_iter().flatten() {
+            if !match_has_trigger(entry, trigger) {
+                continue;
+            }
+
+            if !source_files.iter().any(|f| f == file_name) {
+                source_files.push(file_name.to_string());
+            }
+            local_var_names.extend(var_names(entry.get("vars")));
+            if let Some(replace) = entry.get("replace").and_then(|r| r.as_str()) {
+                replace_texts.push(replace.to_string());
+            }
+        }
+    }
+    source_files.sort();
+
+    let mut project_vars = HashMap::new();
+    let mut unresolved_vars = vec![];
+    for text in &replace_texts {
+        for name in extract_var_refs(text) {
+            if let Some(value) = project_var_values.get(&name) {
+                project_vars.insert(name, value.clone());
+            } else if !name.starts_with("form.")
+                && !local_var_names.contains(&name)
+                && !global_var_names.contains(&name)
+                && !unresolved_vars.contains(&name)
+            {
+                unresolved_vars.push(name);
+            }
+        }
+    }
+
+    Ok(TriggerResolution {
+        trigger: trigger.to_string(),
+        is_live: !live_matches.is_empty(),
+        live_matches,
+        source_files,
+        project_vars,
+        unresolved_vars,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_match_list() {
+        let output = r#"[{"triggers":[":hi",":hello"],"replace":"Hello {{name}}","label":null},{"triggers":[":sig"],"replace":"Regards"}]"#;
+        let matches = parse_match_list(output).unwrap();
+        assert_eq!(matches.len(), 2);
+        assert_eq!(matches[0].triggers, vec![":hi", ":hello"]);
+        assert_eq!(matches[1].replace, "Regards");
+        assert!(matches[1].label.is_none());
+    }
+
+    #[test]
+    fn test_match_has_trigger() {
+        let entry: YamlValue = serde_yaml::from_str("triggers: [':a', ':b']\nreplace: x").unwrap();
+        assert!(match_has_trigger(&entry, ":b"));
+        assert!(!match_has_trigger(&entry, ":c"));
+
+        let entry: YamlValue = serde_yaml::from_str("trigger: ':c'\nreplace: x").unwrap();
+        assert!(match_has_trigger(&entry, ":c"));
+    }
+
+    #[test]
+    fn test_read_project_var_values() {
+        let config: YamlValue = serde_yaml::from_str(
+            "global_vars:\n  - name: directory\n    type: echo\n    params:\n      echo: /tmp/app\n",
+        )
+        .unwrap();
+        let values = read_project_var_values(&config);
+        assert_eq!(
+            values.get("directory").map(String::as_str),
+            Some("/tmp/app")
+        );
+    }
+}
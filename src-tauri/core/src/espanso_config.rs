@@ -0,0 +1,338 @@
+use log::info;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_yaml::Value as YamlValue;
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::paths::get_espanso_config_dir_internal;
+use crate::yaml_utils::atomic_write;
+
+/// The config Espanso applies everywhere; app-specific configs override it
+const DEFAULT_CONFIG_FILE: &str = "default.yml";
+
+const VALID_BACKENDS: [&str; 3] = ["auto", "clipboard", "inject"];
+
+const VALID_TOGGLE_KEYS: [&str; 13] = [
+    "off",
+    "ctrl",
+    "alt",
+    "shift",
+    "meta",
+    "left_ctrl",
+    "right_ctrl",
+    "left_alt",
+    "right_alt",
+    "left_shift",
+    "right_shift",
+    "left_meta",
+    "right_meta",
+];
+
+/// An Espanso config file from `espanso/config/` (default.yml or an app-specific config)
+///
+/// Only the options we manage are typed; everything else is kept in `extra` so it
+/// survives a read/write round trip.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct EspansoAppConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filter_title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filter_class: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filter_exec: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filter_os: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enable: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub backend: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub toggle_key: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub includes: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub excludes: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extra_includes: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extra_excludes: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub use_standard_includes: Option<bool>,
+    #[serde(flatten)]
+    #[serde(default)]
+    pub extra: HashMap<String, YamlValue>,
+}
+
+impl EspansoAppConfig {
+    fn has_filter(&self) -> bool {
+        self.filter_title.is_some()
+            || self.filter_class.is_some()
+            || self.filter_exec.is_some()
+            || self.filter_os.is_some()
+    }
+}
+
+// ========== Paths ==========
+
+/// Get the espanso/config directory, which holds default.yml and app-specific configs
+fn get_app_config_dir() -> Result<PathBuf, String> {
+    let config_dir = get_espanso_config_dir_internal()?.join("config");
+
+    if !config_dir.exists() {
+        fs::create_dir_all(&config_dir)
+            .map_err(|e| format!("Failed to create espanso/config directory: {}", e))?;
+    }
+
+    Ok(config_dir)
+}
+
+/// Check that a config file name is a plain .yml/.yaml file name inside espanso/config
+fn check_file_name(file_name: &str) -> Result<(), String> {
+    if file_name.is_empty()
+        || file_name.contains('/')
+        || file_name.contains('\\')
+        || file_name.starts_with('.')
+    {
+        return Err(format!("Invalid config file name: {}", file_name));
+    }
+
+    if !file_name.ends_with(".yml") && !file_name.ends_with(".yaml") {
+        return Err(format!(
+            "Config file must have a .yml or .yaml extension: {}",
+            file_name
+        ));
+    }
+
+    Ok(())
+}
+
+// ========== Validation ==========
+
+/// Validate an Espanso config, returning a list of problems (empty when valid)
+pub fn validate_app_config(file_name: &str, config: &EspansoAppConfig) -> Vec<String> {
+    let mut errors = vec![];
+
+    if let Err(e) = check_file_name(file_name) {
+        errors.push(e);
+    }
+
+    let filters = [
+        ("filter_title", &config.filter_title),
+        ("filter_class", &config.filter_class),
+        ("filter_exec", &config.filter_exec),
+    ];
+    for (key, filter) in filters {
+        if let Some(pattern) = filter {
+            if pattern.trim().is_empty() {
+                errors.push(format!("{} must not be empty", key));
+            } else if let Err(e) = Regex::new(pattern) {
+                errors.push(format!("{} is not a valid regex: {}", key, e));
+            }
+        }
+    }
+
+    if let Some(os) = &config.filter_os {
+        if !["macos", "windows", "linux"].contains(&os.to_lowercase().as_str()) {
+            errors.push(format!(
+                "filter_os must be one of macos, windows or linux, got '{}'",
+                os
+            ));
+        }
+    }
+
+    if file_name == DEFAULT_CONFIG_FILE {
+        if config.has_filter() {
+            errors.push(format!(
+                "{} applies to every application and cannot have filters",
+                DEFAULT_CONFIG_FILE
+            ));
+        }
+    } else if !config.has_filter() {
+        errors.push(
+            "App-specific configs need at least one of filter_title, filter_class, filter_exec or filter_os"
+                .to_string(),
+        );
+    }
+
+    if let Some(backend) = &config.backend {
+        if !VALID_BACKENDS.contains(&backend.to_lowercase().as_str()) {
+            errors.push(format!(
+                "backend must be one of Auto, Clipboard or Inject, got '{}'",
+                backend
+            ));
+        }
+    }
+
+    if let Some(toggle_key) = &config.toggle_key {
+        if !VALID_TOGGLE_KEYS.contains(&toggle_key.to_lowercase().as_str()) {
+            errors.push(format!("toggle_key '{}' is not a valid key", toggle_key));
+        }
+    }
+
+    let path_lists = [
+        ("includes", &config.includes),
+        ("excludes", &config.excludes),
+        ("extra_includes", &config.extra_includes),
+        ("extra_excludes", &config.extra_excludes),
+    ];
+    for (key, paths) in path_lists {
+        if let Some(paths) = paths {
+            if paths.iter().any(|p| p.trim().is_empty()) {
+                errors.push(format!("{} must not contain empty paths", key));
+            }
+        }
+    }
+
+    errors
+}
+
+// ========== Read / Write ==========
+
+/// List the Espanso config files in espanso/config (our own JSON metadata is skipped)
+pub fn list_app_configs() -> Result<Vec<String>, String> {
+    let config_dir = get_app_config_dir()?;
+
+    let entries = fs::read_dir(&config_dir)
+        .map_err(|e| format!("Failed to read Espanso config directory: {}", e))?;
+
+    let mut files: Vec<String> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file())
+        .filter_map(|path| {
+            path.file_name()
+                .and_then(|n| n.to_str())
+                .map(|n| n.to_string())
+        })
+        .filter(|name| check_file_name(name).is_ok())
+        .collect();
+
+    files.sort();
+    Ok(files)
+}
+
+/// Read and parse an Espanso config file
+pub fn read_app_config(file_name: &str) -> Result<EspansoAppConfig, String> {
+    check_file_name(file_name)?;
+    let path = get_app_config_dir()?.join(file_name);
+
+    if !path.exists() {
+        return Err(format!("Config file not found: {}", file_name));
+    }
+
+    let contents =
+        fs::read_to_string(&path).map_err(|e| format!("Failed to read config file: {}", e))?;
+
+    // An empty file is a valid (empty) config
+    if contents.trim().is_empty() {
+        return Ok(EspansoAppConfig::default());
+    }
+
+    serde_yaml::from_str(&contents).map_err(|e| format!("Failed to parse config YAML: {}", e))
+}
+
+/// Validate and write an Espanso config file
+///
+/// Comments in the existing file are not preserved.
+pub fn write_app_config(file_name: &str, config: &EspansoAppConfig) -> Result<(), String> {
+    let errors = validate_app_config(file_name, config);
+    if !errors.is_empty() {
+        return Err(format!("Invalid Espanso config: {}", errors.join("; ")));
+    }
+
+    let path = get_app_config_dir()?.join(file_name);
+    let yaml_string =
+        serde_yaml::to_string(config).map_err(|e| format!("Failed to serialize YAML: {}", e))?;
+
+    atomic_write(&path, &yaml_string).map_err(|e| format!("Failed to write config file: {}", e))?;
+
+    info!("Wrote Espanso config: {}", file_name);
+    Ok(())
+}
+
+/// Delete an app-specific config file (default.yml cannot be deleted)
+pub fn delete_app_config(file_name: &str) -> Result<(), String> {
+    check_file_name(file_name)?;
+
+    if file_name == DEFAULT_CONFIG_FILE {
+        return Err(format!("Cannot delete {}", DEFAULT_CONFIG_FILE));
+    }
+
+    let path = get_app_config_dir()?.join(file_name);
+    if !path.exists() {
+        return Err(format!("Config file {} not found", file_name));
+    }
+
+    fs::remove_file(&path).map_err(|e| format!("Failed to delete config file: {}", e))?;
+
+    info!("Deleted Espanso config: {}", file_name);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip_preserves_unknown_options() {
+        let yaml = r#"filter_exec: "Terminal|iTerm2"
+backend: Clipboard
+includes:
+  - ../match/terminal/*.yml
+clipboard_threshold: 100
+"#;
+        let config: EspansoAppConfig = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(config.filter_exec.as_deref(), Some("Terminal|iTerm2"));
+        assert_eq!(config.backend.as_deref(), Some("Clipboard"));
+        assert!(config.extra.contains_key("clipboard_threshold"));
+
+        let output = serde_yaml::to_string(&config).unwrap();
+        assert!(output.contains("clipboard_threshold: 100"));
+        assert!(!output.contains("filter_title"));
+    }
+
+    #[test]
+    fn test_validate_app_specific_config() {
+        let config = EspansoAppConfig {
+            filter_exec: Some("Terminal".to_string()),
+            backend: Some("inject".to_string()),
+            toggle_key: Some("LEFT_ALT".to_string()),
+            ..Default::default()
+        };
+        assert!(validate_app_config("terminal.yml", &config).is_empty());
+
+        let invalid = EspansoAppConfig {
+            filter_title: Some("(unclosed".to_string()),
+            backend: Some("Teleport".to_string()),
+            toggle_key: Some("F13".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(validate_app_config("browser.yml", &invalid).len(), 3);
+
+        // App-specific configs without filters never apply
+        assert_eq!(
+            validate_app_config("browser.yml", &EspansoAppConfig::default()).len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_validate_default_config() {
+        assert!(validate_app_config("default.yml", &EspansoAppConfig::default()).is_empty());
+
+        let config = EspansoAppConfig {
+            filter_class: Some("Chrome".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(validate_app_config("default.yml", &config).len(), 1);
+    }
+
+    #[test]
+    fn test_check_file_name() {
+        assert!(check_file_name("terminal.yml").is_ok());
+        assert!(check_file_name("projects.json").is_err());
+        assert!(check_file_name("../match/base.yml").is_err());
+    }
+}
@@ -0,0 +1,413 @@
+//! Run `shell` and `script` extensions so authors can test them before saving
+//!
+//! Execution is off until the user enables it in `execution_settings.json`. Commands
+//! run with a scrubbed environment, in the active project's `directory` (or the temp
+//! directory), and are killed when they exceed the timeout. Output beyond the cap is
+//! dropped.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use crate::paths::get_app_data_file_path;
+use crate::projects::active_project_variables;
+
+/// Environment variables passed through to executed commands; everything else is removed
+const ALLOWED_ENV_VARS: &[&str] = &[
+    "PATH",
+    "HOME",
+    "USER",
+    "USERNAME",
+    "LANG",
+    "LC_ALL",
+    "TMPDIR",
+    "TEMP",
+    "TMP",
+    "SystemRoot",
+    "ComSpec",
+    "USERPROFILE",
+];
+
+/// Project variable used as the working directory
+const WORKING_DIR_VARIABLE: &str = "directory";
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+const OUTPUT_GRACE_PERIOD: Duration = Duration::from_millis(200);
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecutionSettings {
+    /// Explicit opt-in; nothing is executed while this is false
+    pub enabled: bool,
+    pub timeout_ms: u64,
+    pub max_output_bytes: usize,
+}
+
+impl Default for ExecutionSettings {
+    fn default() -> Self {
+        ExecutionSettings {
+            enabled: false,
+            timeout_ms: 5_000,
+            max_output_bytes: 64 * 1024,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecResult {
+    pub stdout: String,
+    pub stderr: String,
+    /// `None` when the process was killed or ended by a signal
+    pub exit_code: Option<i32>,
+    pub timed_out: bool,
+    pub truncated: bool,
+    pub duration_ms: u64,
+    pub working_dir: String,
+}
+
+fn get_execution_settings_file_path() -> Result<PathBuf, String> {
+    get_app_data_file_path("execution_settings.json")
+}
+
+pub fn load_execution_settings() -> Result<ExecutionSettings, String> {
+    let file_path = get_execution_settings_file_path()?;
+
+    if file_path.exists() {
+        let content = fs::read_to_string(&file_path)
+            .map_err(|e| format!("Failed to read execution settings file: {}", e))?;
+        serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse execution settings: {}", e))
+    } else {
+        Ok(ExecutionSettings::default())
+    }
+}
+
+pub fn save_execution_settings(settings: &ExecutionSettings) -> Result<(), String> {
+    let file_path = get_execution_settings_file_path()?;
+
+    if let Some(parent) = file_path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create execution settings directory: {}", e))?;
+    }
+
+    let json = serde_json::to_string_pretty(settings)
+        .map_err(|e| format!("Failed to serialize execution settings: {}", e))?;
+    fs::write(&file_path, json)
+        .map_err(|e| format!("Failed to write execution settings file: {}", e))?;
+
+    Ok(())
+}
+
+/// The active project's `directory` variable if it exists, else the temp directory
+pub fn resolve_working_dir() -> Result<PathBuf, String> {
+    let project_dir = active_project_variables()?.and_then(|(_, values)| {
+        values
+            .into_iter()
+            .find(|(name, _)| name == WORKING_DIR_VARIABLE)
+            .map(|(_, value)| PathBuf::from(value.trim()))
+    });
+
+    Ok(project_dir
+        .filter(|dir| dir.is_dir())
+        .unwrap_or_else(std::env::temp_dir))
+}
+
+fn string_param<'v>(extension: &'v Value, param: &str) -> Option<&'v str> {
+    extension
+        .get("params")
+        .and_then(|p| p.get(param))
+        .and_then(|v| v.as_str())
+}
+
+/// Build the command line for a `shell` extension the way Espanso does
+fn shell_command(extension: &Value) -> Result<Command, String> {
+    let cmd = string_param(extension, "cmd").ok_or("Shell extension has no 'cmd'")?;
+    let default_shell = if cfg!(target_os = "windows") {
+        "cmd"
+    } else {
+        "sh"
+    };
+
+    let mut command = match string_param(extension, "shell").unwrap_or(default_shell) {
+        "cmd" => {
+            let mut c = Command::new("cmd");
+            c.args(["/C", cmd]);
+            c
+        }
+        shell @ ("powershell" | "pwsh") => {
+            let mut c = Command::new(shell);
+            c.args(["-Command", cmd]);
+            c
+        }
+        "wsl" => {
+            let mut c = Command::new("wsl");
+            c.args(["bash", "-c", cmd]);
+            c
+        }
+        shell @ ("sh" | "bash" | "zsh") => {
+            let mut c = Command::new(shell);
+            c.args(["-c", cmd]);
+            c
+        }
+        other => return Err(format!("Unsupported shell: {}", other)),
+    };
+    command.stdin(Stdio::null());
+    Ok(command)
+}
+
+/// Build the command line for a `script` extension
+///
+/// Inline scripts (`interpreter` + `script`) are written to a temporary file, which
+/// is returned so it outlives the process. Espanso-style `args` lists are run as is.
+fn script_command(extension: &Value) -> Result<(Command, Option<tempfile::NamedTempFile>), String> {
+    let args: Vec<&str> = extension
+        .get("params")
+        .and_then(|p| p.get("args"))
+        .and_then(|a| a.as_array())
+        .map(|a| a.iter().filter_map(|v| v.as_str()).collect())
+        .unwrap_or_default();
+
+    match (
+        string_param(extension, "interpreter"),
+        string_param(extension, "script"),
+    ) {
+        (Some(interpreter), Some(script)) => {
+            let mut file = tempfile::NamedTempFile::new()
+                .map_err(|e| format!("Failed to create script file: {}", e))?;
+            file.write_all(script.as_bytes())
+                .map_err(|e| format!("Failed to write script file: {}", e))?;
+
+            let mut command = Command::new(interpreter);
+            command.arg(file.path()).args(&args).stdin(Stdio::null());
+            Ok((command, Some(file)))
+        }
+        _ => {
+            let (program, rest) = args
+                .split_first()
+                .ok_or("Script extension has neither a script nor args")?;
+            let mut command = Command::new(program);
+            command.args(rest).stdin(Stdio::null());
+            Ok((command, None))
+        }
+    }
+}
+
+type CapturedOutput = Arc<Mutex<(Vec<u8>, bool)>>;
+
+/// Read a stream to the end in the background, keeping at most `cap` bytes
+fn read_capped<R: Read + Send + 'static>(
+    mut reader: R,
+    cap: usize,
+) -> (CapturedOutput, JoinHandle<()>) {
+    let output: CapturedOutput = Arc::new(Mutex::new((vec![], false)));
+    let captured = output.clone();
+
+    let handle = thread::spawn(move || {
+        let mut buffer = [0u8; 8192];
+
+        // Keep draining past the cap so the child never blocks on a full pipe
+        while let Ok(read) = reader.read(&mut buffer) {
+            if read == 0 {
+                break;
+            }
+            let mut captured = captured.lock().unwrap_or_else(|e| e.into_inner());
+            let room = cap.saturating_sub(captured.0.len());
+            captured.0.extend_from_slice(&buffer[..read.min(room)]);
+            captured.1 |= read > room;
+        }
+    });
+
+    (output, handle)
+}
+
+/// Collect captured output, giving the reader a moment to finish
+///
+/// Background processes started by the command can keep the pipe open after it
+/// exits, so the reader is not waited on indefinitely.
+fn collect_output((output, handle): (CapturedOutput, JoinHandle<()>)) -> (Vec<u8>, bool) {
+    let deadline = Instant::now() + OUTPUT_GRACE_PERIOD;
+    while !handle.is_finished() && Instant::now() < deadline {
+        thread::sleep(POLL_INTERVAL);
+    }
+
+    let output = output.lock().unwrap_or_else(|e| e.into_inner());
+    (output.0.clone(), output.1)
+}
+
+/// Kill the child and, on Unix, the rest of its process group
+fn kill_process_tree(child: &mut Child) {
+    #[cfg(unix)]
+    {
+        let _ = Command::new("kill")
+            .args(["-KILL", &format!("-{}", child.id())])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status();
+    }
+    let _ = child.kill();
+    let _ = child.wait();
+}
+
+/// Wait for the child, killing it at the deadline; returns the exit code and whether it timed out
+fn wait_with_timeout(child: &mut Child, timeout: Duration) -> Result<(Option<i32>, bool), String> {
+    let deadline = Instant::now() + timeout;
+
+    loop {
+        if let Some(status) = child
+            .try_wait()
+            .map_err(|e| format!("Failed to wait for process: {}", e))?
+        {
+            return Ok((status.code(), false));
+        }
+        if Instant::now() >= deadline {
+            kill_process_tree(child);
+            return Ok((None, true));
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+/// Run a `shell` or `script` extension without checking the opt-in
+pub fn execute_extension(
+    extension: &Value,
+    settings: &ExecutionSettings,
+    working_dir: &Path,
+) -> Result<ExecResult, String> {
+    let extension_type = extension
+        .get("type")
+        .and_then(|t| t.as_str())
+        .unwrap_or_default();
+
+    let (mut command, _script_file) = match extension_type {
+        "shell" => (shell_command(extension)?, None),
+        "script" => script_command(extension)?,
+        other => return Err(format!("Cannot execute '{}' extensions", other)),
+    };
+
+    command.env_clear().current_dir(working_dir);
+    for name in ALLOWED_ENV_VARS {
+        if let Some(value) = std::env::var_os(name) {
+            command.env(name, value);
+        }
+    }
+    command.stdout(Stdio::piped()).stderr(Stdio::piped());
+
+    // Run in a separate process group so a timeout can kill everything the command started
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        command.process_group(0);
+    }
+
+    let started = Instant::now();
+    let mut child = command
+        .spawn()
+        .map_err(|e| format!("Failed to start {} extension: {}", extension_type, e))?;
+
+    let stdout = read_capped(
+        child.stdout.take().ok_or("Failed to capture stdout")?,
+        settings.max_output_bytes,
+    );
+    let stderr = read_capped(
+        child.stderr.take().ok_or("Failed to capture stderr")?,
+        settings.max_output_bytes,
+    );
+
+    let (exit_code, timed_out) =
+        wait_with_timeout(&mut child, Duration::from_millis(settings.timeout_ms))?;
+
+    let (stdout, stdout_truncated) = collect_output(stdout);
+    let (stderr, stderr_truncated) = collect_output(stderr);
+
+    let mut stdout = String::from_utf8_lossy(&stdout).into_owned();
+    // Espanso trims shell output unless `trim: false`
+    let trim = extension
+        .get("params")
+        .and_then(|p| p.get("trim"))
+        .and_then(|t| t.as_bool())
+        .unwrap_or(true);
+    if extension_type == "shell" && trim {
+        stdout = stdout.trim().to_string();
+    }
+
+    Ok(ExecResult {
+        stdout,
+        stderr: String::from_utf8_lossy(&stderr).into_owned(),
+        exit_code,
+        timed_out,
+        truncated: stdout_truncated || stderr_truncated,
+        duration_ms: started.elapsed().as_millis() as u64,
+        working_dir: working_dir.display().to_string(),
+    })
+}
+
+/// Run a `shell` or `script` extension if the user has enabled execution
+pub fn run_extension(extension: &Value) -> Result<ExecResult, String> {
+    let settings = load_execution_settings()?;
+    if !settings.enabled {
+        return Err(
+            "Running shell and script extensions is disabled. Enable it in the execution settings first."
+                .to_string(),
+        );
+    }
+
+    execute_extension(extension, &settings, &resolve_working_dir()?)
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn settings() -> ExecutionSettings {
+        ExecutionSettings {
+            enabled: true,
+            timeout_ms: 2_000,
+            max_output_bytes: 1024,
+        }
+    }
+
+    #[test]
+    fn test_execute_shell_extension() {
+        std::env::set_var("BRM_TEST_SECRET", "leak");
+        let extension = json!({
+            "type": "shell",
+            "params": { "cmd": "pwd; echo \"[$BRM_TEST_SECRET]\"; echo oops >&2; exit 3" }
+        });
+        let dir = std::env::temp_dir().canonicalize().unwrap();
+
+        let result = execute_extension(&extension, &settings(), &dir).unwrap();
+        assert_eq!(result.stdout, format!("{}\n[]", dir.display()));
+        assert_eq!(result.stderr, "oops\n");
+        assert_eq!(result.exit_code, Some(3));
+        assert!(!result.timed_out);
+    }
+
+    #[test]
+    fn test_execute_script_with_timeout_and_cap() {
+        let extension = json!({
+            "type": "script",
+            "params": { "interpreter": "sh", "script": "head -c 4096 /dev/zero; sleep 5" }
+        });
+        let mut settings = settings();
+        settings.timeout_ms = 200;
+
+        let result = execute_extension(&extension, &settings, &std::env::temp_dir()).unwrap();
+        assert!(result.timed_out);
+        assert!(result.truncated);
+        assert_eq!(result.stdout.len(), 1024);
+        assert_eq!(result.exit_code, None);
+    }
+
+    #[test]
+    fn test_execute_rejects_other_types() {
+        let extension = json!({ "type": "echo", "params": { "echo": "hi" } });
+        assert!(execute_extension(&extension, &settings(), &std::env::temp_dir()).is_err());
+    }
+}
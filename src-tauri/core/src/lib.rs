@@ -0,0 +1,18 @@
+//! Core project, variable and Espanso file logic for BetterReplacementsManager
+//!
+//! Everything here works without the GUI, so it is shared by the Tauri app and
+//! the `brm` command-line tool.
+
+pub mod categories;
+pub mod espanso;
+pub mod espanso_cli;
+pub mod espanso_config;
+pub mod exec;
+pub mod extensions;
+pub mod mcp;
+pub mod paths;
+pub mod projects;
+pub mod render;
+pub mod server;
+pub mod variables;
+pub mod yaml_utils;
@@ -0,0 +1,398 @@
+//! Model Context Protocol server over stdio
+//!
+//! `brm mcp` lets AI coding agents read the active project's variables and the
+//! snippet libraries in the Espanso match directory, search and render snippets,
+//! and switch the active project. Messages are newline-delimited JSON-RPC 2.0 on
+//! stdin/stdout; logs go to stderr.
+
+use log::{info, warn};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::io::{BufRead, Write};
+
+use crate::espanso::{list_espanso_yaml_files, read_espanso_file, Replacement};
+use crate::paths::get_espanso_file_path;
+use crate::projects::{active_project_variables, load_project_data, set_active_project};
+use crate::render::{load_render_context, render_replacement};
+use crate::server::{
+    error_response, RpcError, INVALID_PARAMS, INVALID_REQUEST, METHOD_NOT_FOUND, PARSE_ERROR,
+};
+
+const PROTOCOL_VERSION: &str = "2024-11-05";
+const ACTIVE_PROJECT_URI: &str = "brm://project/active";
+const SNIPPETS_URI_PREFIX: &str = "brm://snippets/";
+const DEFAULT_SEARCH_LIMIT: usize = 20;
+
+// ========== Resources ==========
+
+fn active_project_resource() -> Result<Value, String> {
+    Ok(match active_project_variables()? {
+        Some((project, values)) => json!({
+            "project": project,
+            "variables": values.into_iter().collect::<HashMap<_, _>>(),
+        }),
+        None => json!({ "project": null, "variables": {} }),
+    })
+}
+
+fn list_resources() -> Result<Value, String> {
+    let mut resources = vec![json!({
+        "uri": ACTIVE_PROJECT_URI,
+        "name": "Active project variables",
+        "description": "The active project and its variable values (e.g. directory, tech_stack)",
+        "mimeType": "application/json",
+    })];
+
+    for file in list_espanso_yaml_files()? {
+        resources.push(json!({
+            "uri": format!("{}{}", SNIPPETS_URI_PREFIX, file),
+            "name": file,
+            "description": format!("Snippets in the Espanso match file {}", file),
+            "mimeType": "application/json",
+        }));
+    }
+
+    Ok(json!({ "resources": resources }))
+}
+
+/// Read the snippets of a match file given by bare file name
+fn read_snippet_file(file: &str) -> Result<Vec<Replacement>, String> {
+    if file.contains('/') || file.contains('\\') || file.starts_with('.') {
+        return Err(format!("Invalid match file name: {}", file));
+    }
+    read_espanso_file(get_espanso_file_path(file)?.display().to_string())
+}
+
+fn read_resource(params: &Value) -> Result<Value, RpcError> {
+    let uri = params
+        .get("uri")
+        .and_then(|u| u.as_str())
+        .ok_or_else(|| RpcError::new(INVALID_PARAMS, "Missing string param 'uri'"))?;
+
+    let contents = if uri == ACTIVE_PROJECT_URI {
+        active_project_resource()?
+    } else if let Some(file) = uri.strip_prefix(SNIPPETS_URI_PREFIX) {
+        serde_json::to_value(read_snippet_file(file)?)
+            .map_err(|e| format!("Failed to serialize snippets: {}", e))?
+    } else {
+        return Err(RpcError::new(
+            INVALID_PARAMS,
+            format!("Unknown resource: {}", uri),
+        ));
+    };
+
+    let text = serde_json::to_string_pretty(&contents)
+        .map_err(|e| format!("Failed to serialize resource: {}", e))?;
+    Ok(json!({
+        "contents": [{ "uri": uri, "mimeType": "application/json", "text": text }]
+    }))
+}
+
+// ========== Tools ==========
+
+fn list_tools() -> Value {
+    json!({
+        "tools": [
+            {
+                "name": "search_snippets",
+                "description": "Search Espanso snippets by trigger, label or replacement text",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "query": { "type": "string", "description": "Case-insensitive text to look for" },
+                        "file": { "type": "string", "description": "Only search this match file" },
+                        "limit": { "type": "integer", "description": "Maximum number of results" }
+                    },
+                    "required": ["query"]
+                }
+            },
+            {
+                "name": "render_snippet",
+                "description": "Render a snippet with the active project's variables and custom variables",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "trigger": { "type": "string" },
+                        "file": { "type": "string", "description": "Match file to look in when the trigger is ambiguous" },
+                        "values": { "type": "object", "description": "Variable values overriding the stored ones" },
+                        "formValues": { "type": "object", "description": "Values for {{form.field}} references, keyed by field" },
+                        "choices": { "type": "object", "description": "Chosen value for choice and random vars, keyed by var name" }
+                    },
+                    "required": ["trigger"]
+                }
+            },
+            {
+                "name": "switch_project",
+                "description": "Make a project active by id or name, or clear the active project with null",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "project": { "type": ["string", "null"] }
+                    },
+                    "required": ["project"]
+                }
+            }
+        ]
+    })
+}
+
+/// Load snippets from one match file, or from every match file
+fn load_snippets(file: Option<&str>) -> Result<Vec<Replacement>, String> {
+    match file {
+        Some(file) => read_snippet_file(file),
+        None => {
+            let mut snippets = vec![];
+            for file in list_espanso_yaml_files()? {
+                match read_snippet_file(&file) {
+                    Ok(replacements) => snippets.extend(replacements),
+                    Err(e) => warn!("Skipping {}: {}", file, e),
+                }
+            }
+            Ok(snippets)
+        }
+    }
+}
+
+fn search_snippets(args: &Value) -> Result<Value, String> {
+    let query = args
+        .get("query")
+        .and_then(|q| q.as_str())
+        .ok_or("Missing 'query'")?
+        .to_lowercase();
+    let limit = args
+        .get("limit")
+        .and_then(|l| l.as_u64())
+        .map(|l| l as usize)
+        .unwrap_or(DEFAULT_SEARCH_LIMIT);
+
+    let results: Vec<Value> = load_snippets(args.get("file").and_then(|f| f.as_str()))?
+        .into_iter()
+        .filter(|r| {
+            let label = r
+                .metadata
+                .get("label")
+                .and_then(|l| l.as_str())
+                .unwrap_or_default();
+            r.trigger.to_lowercase().contains(&query)
+                || r.replace.to_lowercase().contains(&query)
+                || label.to_lowercase().contains(&query)
+        })
+        .take(limit)
+        .map(|r| {
+            json!({
+                "file": r.source,
+                "trigger": r.trigger,
+                "replace": r.replace,
+                "label": r.metadata.get("label"),
+            })
+        })
+        .collect();
+
+    Ok(json!(results))
+}
+
+/// Read a JSON object of strings (other JSON values are stringified)
+fn string_map(value: Option<&Value>) -> HashMap<String, String> {
+    value
+        .and_then(|v| v.as_object())
+        .map(|map| {
+            map.iter()
+                .map(|(name, value)| {
+                    let value = value
+                        .as_str()
+                        .map(|s| s.to_string())
+                        .unwrap_or_else(|| value.to_string());
+                    (name.clone(), value)
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn render_snippet(args: &Value) -> Result<Value, String> {
+    let trigger = args
+        .get("trigger")
+        .and_then(|t| t.as_str())
+        .ok_or("Missing 'trigger'")?;
+
+    let snippet = load_snippets(args.get("file").and_then(|f| f.as_str()))?
+        .into_iter()
+        .find(|r| r.trigger == trigger)
+        .ok_or_else(|| format!("Snippet not found: {}", trigger))?;
+
+    let mut ctx = load_render_context()?;
+    ctx.overrides = string_map(args.get("values"));
+    ctx.form_values = string_map(args.get("formValues"));
+    ctx.choices = string_map(args.get("choices"));
+
+    let result = render_replacement(&snippet, &ctx)?;
+    Ok(json!({
+        "file": snippet.source,
+        "trigger": snippet.trigger,
+        "rendered": result.text,
+        "cursorPosition": result.cursor_position,
+        "unresolved": result.unresolved,
+        "trace": result.trace,
+    }))
+}
+
+fn switch_project(args: &Value) -> Result<Value, String> {
+    let id = match args.get("project") {
+        None | Some(Value::Null) => None,
+        Some(Value::String(key)) => {
+            let data = load_project_data()?;
+            let project = data
+                .projects
+                .iter()
+                .find(|p| p.id == *key || p.name.eq_ignore_ascii_case(key))
+                .ok_or_else(|| format!("Project not found: {}", key))?;
+            Some(project.id.clone())
+        }
+        Some(_) => return Err("'project' must be a string or null".to_string()),
+    };
+
+    set_active_project(id)?;
+    active_project_resource()
+}
+
+fn call_tool(params: &Value) -> Result<Value, RpcError> {
+    let name = params
+        .get("name")
+        .and_then(|n| n.as_str())
+        .ok_or_else(|| RpcError::new(INVALID_PARAMS, "Missing string param 'name'"))?;
+    let args = params.get("arguments").cloned().unwrap_or(json!({}));
+
+    let result = match name {
+        "search_snippets" => search_snippets(&args),
+        "render_snippet" => render_snippet(&args),
+        "switch_project" => switch_project(&args),
+        _ => {
+            return Err(RpcError::new(
+                INVALID_PARAMS,
+                format!("Unknown tool: {}", name),
+            ))
+        }
+    };
+
+    // Tool failures are reported in the result so the agent can see and recover from them
+    Ok(match result {
+        Ok(value) => {
+            let text = serde_json::to_string_pretty(&value)
+                .map_err(|e| format!("Failed to serialize tool result: {}", e))?;
+            json!({ "content": [{ "type": "text", "text": text }], "isError": false })
+        }
+        Err(e) => json!({ "content": [{ "type": "text", "text": e }], "isError": true }),
+    })
+}
+
+// ========== Protocol ==========
+
+fn initialize(params: &Value) -> Value {
+    let protocol_version = params
+        .get("protocolVersion")
+        .and_then(|v| v.as_str())
+        .unwrap_or(PROTOCOL_VERSION);
+
+    json!({
+        "protocolVersion": protocol_version,
+        "capabilities": { "resources": {}, "tools": {} },
+        "serverInfo": { "name": "brm", "version": env!("CARGO_PKG_VERSION") },
+    })
+}
+
+fn dispatch(method: &str, params: &Value) -> Result<Value, RpcError> {
+    match method {
+        "initialize" => Ok(initialize(params)),
+        "ping" => Ok(json!({})),
+        "resources/list" => Ok(list_resources()?),
+        "resources/read" => read_resource(params),
+        "tools/list" => Ok(list_tools()),
+        "tools/call" => call_tool(params),
+        _ => Err(RpcError::new(
+            METHOD_NOT_FOUND,
+            format!("Method not found: {}", method),
+        )),
+    }
+}
+
+/// Handle one line of input, returning the response line if the message needs one
+pub fn handle_message(line: &str) -> Option<Value> {
+    let message: Value = match serde_json::from_str(line) {
+        Ok(message) => message,
+        Err(e) => {
+            return Some(error_response(
+                Value::Null,
+                RpcError::new(PARSE_ERROR, format!("Parse error: {}", e)),
+            ))
+        }
+    };
+
+    let id = message.get("id").cloned();
+    let Some(method) = message.get("method").and_then(|m| m.as_str()) else {
+        // Responses from the client (we never send requests) need no reply
+        return id.is_none().then(|| {
+            error_response(
+                Value::Null,
+                RpcError::new(INVALID_REQUEST, "Invalid JSON-RPC 2.0 request"),
+            )
+        });
+    };
+
+    // Notifications such as notifications/initialized carry no id and get no reply
+    let id = id?;
+    let params = message.get("params").cloned().unwrap_or(Value::Null);
+    Some(match dispatch(method, &params) {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err(e) => error_response(id, e),
+    })
+}
+
+/// Serve MCP on stdin/stdout until stdin closes
+pub fn serve_stdio() -> Result<(), String> {
+    info!("MCP server started on stdio");
+    let stdin = std::io::stdin();
+    let mut stdout = std::io::stdout();
+
+    for line in stdin.lock().lines() {
+        let line = line.map_err(|e| format!("Failed to read stdin: {}", e))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        if let Some(response) = handle_message(&line) {
+            writeln!(stdout, "{}", response)
+                .and_then(|_| stdout.flush())
+                .map_err(|e| format!("Failed to write stdout: {}", e))?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_handle_message() {
+        let response = handle_message(
+            r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{"protocolVersion":"2025-03-26"}}"#,
+        )
+        .unwrap();
+        assert_eq!(response["result"]["protocolVersion"], "2025-03-26");
+        assert_eq!(response["result"]["serverInfo"]["name"], "brm");
+
+        assert!(
+            handle_message(r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#).is_none()
+        );
+
+        let response = handle_message(r#"{"jsonrpc":"2.0","id":2,"method":"tools/list"}"#).unwrap();
+        assert_eq!(response["result"]["tools"].as_array().unwrap().len(), 3);
+
+        let response = handle_message(
+            r#"{"jsonrpc":"2.0","id":3,"method":"resources/read","params":{"uri":"brm://other"}}"#,
+        )
+        .unwrap();
+        assert_eq!(response["error"]["code"], INVALID_PARAMS);
+    }
+}
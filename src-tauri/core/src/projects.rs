@@ -0,0 +1,810 @@
+use log::info;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+use crate::categories::load_project_categories_data;
+use crate::paths;
+use crate::yaml_utils::{atomic_write, escape_yaml_value};
+
+#[derive(Debug, Deserialize)]
+struct RawProject {
+    id: Option<String>,
+    name: String,
+    description: Option<String>,
+    #[serde(rename = "categoryId")]
+    category_id: Option<String>,
+    #[serde(rename = "isActive")]
+    is_active: Option<bool>,
+    #[serde(rename = "createdAt")]
+    created_at: Option<String>,
+    #[serde(rename = "updatedAt")]
+    updated_at: Option<String>,
+    #[serde(rename = "categoryValues")]
+    category_values: Option<HashMap<String, HashMap<String, String>>>,
+    stack: Option<String>,
+    directory: Option<String>,
+    #[serde(rename = "restartCommand")]
+    restart_command: Option<String>,
+    #[serde(rename = "logCommand")]
+    log_command: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawProjectData {
+    projects: Vec<RawProject>,
+    #[serde(rename = "activeProjectId", default)]
+    active_project_id: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Project {
+    pub id: String,
+    pub name: String,
+    pub description: Option<String>,
+    #[serde(rename = "categoryId")]
+    pub category_id: String,
+    #[serde(rename = "isActive")]
+    pub is_active: bool,
+    #[serde(rename = "createdAt")]
+    pub created_at: String,
+    #[serde(rename = "updatedAt")]
+    pub updated_at: String,
+    // Category-based variable system - all project data stored here
+    #[serde(rename = "categoryValues")]
+    pub category_values:
+        Option<std::collections::HashMap<String, std::collections::HashMap<String, String>>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProjectData {
+    pub projects: Vec<Project>,
+    #[serde(rename = "activeProjectId")]
+    pub active_project_id: Option<String>,
+}
+
+fn get_projects_file_path() -> PathBuf {
+    use crate::paths::get_espanso_config_dir_internal;
+    let espanso_config =
+        get_espanso_config_dir_internal().expect("Failed to get espanso config directory");
+    let config_subdir = espanso_config.join("config");
+
+    // Ensure config subdirectory exists
+    if !config_subdir.exists() {
+        fs::create_dir_all(&config_subdir).expect("Failed to create espanso/config directory");
+    }
+
+    config_subdir.join("projects.json")
+}
+
+fn ensure_app_dir() -> Result<(), String> {
+    let projects_path = get_projects_file_path();
+    if let Some(parent) = projects_path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create app directory: {}", e))?;
+    }
+    Ok(())
+}
+
+fn normalize_projects(
+    raw_projects: Vec<RawProject>,
+    mut active_project_id: Option<String>,
+) -> (Vec<Project>, Option<String>, bool) {
+    let mut migrated = false;
+    let mut projects: Vec<Project> = Vec::with_capacity(raw_projects.len());
+
+    for raw in raw_projects {
+        let (project, changed) = normalize_project(raw);
+        if changed {
+            migrated = true;
+        }
+        projects.push(project);
+    }
+
+    if let Some(ref id) = active_project_id {
+        if !projects.iter().any(|project| &project.id == id) {
+            active_project_id = None;
+            migrated = true;
+        }
+    } else if let Some(active_project) = projects.iter().find(|project| project.is_active) {
+        active_project_id = Some(active_project.id.clone());
+        migrated = true;
+    }
+
+    let mut ensure_active_flag_consistency = false;
+    for project in &projects {
+        let should_be_active = active_project_id
+            .as_ref()
+            .map(|id| id == &project.id)
+            .unwrap_or(false);
+        if project.is_active != should_be_active {
+            ensure_active_flag_consistency = true;
+            break;
+        }
+    }
+
+    if ensure_active_flag_consistency {
+        migrated = true;
+        for project in projects.iter_mut() {
+            let should_be_active = active_project_id
+                .as_ref()
+                .map(|id| id == &project.id)
+                .unwrap_or(false);
+            project.is_active = should_be_active;
+        }
+    }
+
+    (projects, active_project_id, migrated)
+}
+
+fn archive_legacy_file(path: &Path) {
+    let file_stem = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("projects");
+    let backup_filename = format!("{file_stem}.migrated.bak.json");
+    let backup_path = path.with_file_name(backup_filename);
+
+    if let Err(rename_err) = fs::rename(path, &backup_path) {
+        match fs::copy(path, &backup_path) {
+            Ok(_) => {
+                let _ = fs::remove_file(path);
+            }
+            Err(copy_err) => {
+                log::error!(
+                    "Failed to archive legacy projects file {}: rename error: {}, copy error: {}",
+                    path.display(),
+                    rename_err,
+                    copy_err
+                );
+            }
+        }
+    }
+}
+
+fn migrate_legacy_project_file() -> Result<Option<ProjectData>, String> {
+    let legacy_candidates = [
+        "projects.json",
+        "projects.legacy.json",
+        "projects.backup.json",
+    ];
+
+    for filename in legacy_candidates {
+        let Ok(legacy_path) = paths::get_app_data_file_path(filename) else {
+            continue;
+        };
+
+        if !legacy_path.exists() {
+            continue;
+        }
+
+        let contents = fs::read_to_string(&legacy_path)
+            .map_err(|e| format!("Failed to read legacy projects file: {}", e))?;
+
+        if contents.trim().is_empty() {
+            continue;
+        }
+
+        let parsed_raw: Result<RawProjectData, _> = serde_json::from_str(&contents);
+        let (raw_projects, active_project_id) = match parsed_raw {
+            Ok(data) => (data.projects, data.active_project_id),
+            Err(_) => {
+                let parsed_list: Vec<RawProject> = serde_json::from_str(&contents)
+                    .map_err(|e| format!("Failed to parse legacy projects data: {}", e))?;
+                (parsed_list, None)
+            }
+        };
+
+        if raw_projects.is_empty() {
+            continue;
+        }
+
+        let (projects, active_project_id, migrated) =
+            normalize_projects(raw_projects, active_project_id);
+
+        let data = ProjectData {
+            projects,
+            active_project_id,
+        };
+
+        save_project_data(&data)?;
+        archive_legacy_file(&legacy_path);
+
+        if migrated {
+            info!(
+                "Migrated legacy projects file {} to category-aware schema.",
+                filename
+            );
+        } else {
+            info!(
+                "Legacy projects file {} already conformed to new schema.",
+                filename
+            );
+        }
+
+        return Ok(Some(data));
+    }
+
+    Ok(None)
+}
+
+pub fn load_project_data() -> Result<ProjectData, String> {
+    ensure_app_dir()?;
+    let projects_path = get_projects_file_path();
+
+    if !projects_path.exists() {
+        if let Some(migrated) = migrate_legacy_project_file()? {
+            return Ok(migrated);
+        }
+        // Create default data
+        let default_data = ProjectData {
+            projects: vec![],
+            active_project_id: None,
+        };
+        save_project_data(&default_data)?;
+        return Ok(default_data);
+    }
+
+    let contents = fs::read_to_string(&projects_path)
+        .map_err(|e| format!("Failed to read projects file: {}", e))?;
+
+    let raw_data: RawProjectData = serde_json::from_str(&contents)
+        .map_err(|e| format!("Failed to parse projects data: {}", e))?;
+    let RawProjectData {
+        projects: raw_projects,
+        active_project_id: raw_active_project_id,
+    } = raw_data;
+
+    if raw_projects.is_empty() {
+        if let Some(migrated) = migrate_legacy_project_file()? {
+            return Ok(migrated);
+        }
+    }
+
+    let (projects, active_project_id, migrated) =
+        normalize_projects(raw_projects, raw_active_project_id);
+
+    let data = ProjectData {
+        projects,
+        active_project_id,
+    };
+
+    if migrated {
+        save_project_data(&data)?;
+    }
+
+    Ok(data)
+}
+
+pub fn save_project_data(data: &ProjectData) -> Result<(), String> {
+    ensure_app_dir()?;
+    let projects_path = get_projects_file_path();
+
+    let json_string = serde_json::to_string_pretty(data)
+        .map_err(|e| format!("Failed to serialize project data: {}", e))?;
+
+    fs::write(&projects_path, json_string)
+        .map_err(|e| format!("Failed to write projects file: {}", e))?;
+
+    Ok(())
+}
+
+fn normalize_project(raw: RawProject) -> (Project, bool) {
+    fn first_non_empty(map: Option<&HashMap<String, String>>, keys: &[&str]) -> Option<String> {
+        map.and_then(|m| {
+            for key in keys {
+                if let Some(value) = m.get(*key) {
+                    if !value.trim().is_empty() {
+                        return Some(value.clone());
+                    }
+                }
+            }
+            None
+        })
+    }
+
+    fn ensure_field(map: &mut HashMap<String, String>, key: &str, value: &str) -> bool {
+        match map.get(key) {
+            Some(existing) if existing == value => false,
+            Some(existing) if value.trim().is_empty() && !existing.trim().is_empty() => false,
+            _ => {
+                map.insert(key.to_string(), value.to_string());
+                true
+            }
+        }
+    }
+
+    let RawProject {
+        id: raw_id,
+        name,
+        description,
+        category_id,
+        is_active,
+        created_at,
+        updated_at,
+        category_values: raw_category_values,
+        stack,
+        directory,
+        restart_command,
+        log_command,
+    } = raw;
+
+    let migration_timestamp = chrono::Utc::now().to_rfc3339();
+    let mut changed = false;
+
+    let id = raw_id.unwrap_or_else(|| {
+        changed = true;
+        Uuid::new_v4().to_string()
+    });
+
+    let created_at = created_at.unwrap_or_else(|| {
+        changed = true;
+        migration_timestamp.clone()
+    });
+
+    let mut updated_at = updated_at.unwrap_or_else(|| {
+        changed = true;
+        migration_timestamp.clone()
+    });
+
+    let is_active = is_active.unwrap_or(false);
+
+    let has_dev_fields = stack
+        .as_ref()
+        .map(|s| !s.trim().is_empty())
+        .unwrap_or(false)
+        || directory
+            .as_ref()
+            .map(|s| !s.trim().is_empty())
+            .unwrap_or(false)
+        || restart_command
+            .as_ref()
+            .map(|s| !s.trim().is_empty())
+            .unwrap_or(false)
+        || log_command
+            .as_ref()
+            .map(|s| !s.trim().is_empty())
+            .unwrap_or(false);
+
+    let mut category_id = category_id.unwrap_or_else(|| "".to_string());
+    if category_id.trim().is_empty() {
+        category_id = if has_dev_fields {
+            "development".to_string()
+        } else {
+            "general".to_string()
+        };
+        changed = true;
+    }
+
+    let mut category_values = match raw_category_values {
+        Some(values) => values,
+        None => {
+            changed = true;
+            HashMap::new()
+        }
+    };
+
+    let inserted_general = !category_values.contains_key("general");
+    let general_entry = category_values
+        .entry("general".to_string())
+        .or_insert_with(HashMap::new);
+    if inserted_general {
+        changed = true;
+    }
+
+    let name_value = if !name.trim().is_empty() {
+        name.clone()
+    } else {
+        first_non_empty(
+            Some(&*general_entry),
+            &["project_name", "active_project_name"],
+        )
+        .unwrap_or_else(|| "Unnamed Project".to_string())
+    };
+
+    if ensure_field(general_entry, "project_name", &name_value) {
+        changed = true;
+    }
+    if ensure_field(general_entry, "active_project_name", &name_value) {
+        changed = true;
+    }
+
+    let description_value = description.clone().unwrap_or_else(|| {
+        first_non_empty(Some(&*general_entry), &["project_description"]).unwrap_or_default()
+    });
+    if ensure_field(general_entry, "project_description", &description_value) {
+        changed = true;
+    }
+
+    let normalized_description = if description_value.trim().is_empty() {
+        None
+    } else {
+        Some(description_value.clone())
+    };
+    if normalized_description != description {
+        changed = true;
+    }
+
+    let stack_value = stack.unwrap_or_else(|| {
+        first_non_empty(
+            category_values.get("development"),
+            &["tech_stack", "active_project_stack"],
+        )
+        .unwrap_or_default()
+    });
+
+    let directory_value = directory.unwrap_or_else(|| {
+        first_non_empty(
+            category_values.get("development"),
+            &["directory", "active_project_directory"],
+        )
+        .unwrap_or_default()
+    });
+
+    let restart_value = restart_command.unwrap_or_else(|| {
+        first_non_empty(
+            category_values.get("development"),
+            &["restart_command", "active_project_restart_cmd"],
+        )
+        .unwrap_or_default()
+    });
+
+    let log_value = log_command.unwrap_or_else(|| {
+        first_non_empty(
+            category_values.get("development"),
+            &["log_command", "active_project_log_cmd"],
+        )
+        .unwrap_or_default()
+    });
+
+    if has_dev_fields
+        || category_id == "development"
+        || !stack_value.trim().is_empty()
+        || !directory_value.trim().is_empty()
+        || !restart_value.trim().is_empty()
+        || !log_value.trim().is_empty()
+    {
+        let inserted_development = !category_values.contains_key("development");
+        let development_entry = category_values
+            .entry("development".to_string())
+            .or_insert_with(HashMap::new);
+        if inserted_development {
+            changed = true;
+        }
+
+        if ensure_field(development_entry, "tech_stack", &stack_value) {
+            changed = true;
+        }
+        if ensure_field(development_entry, "active_project_stack", &stack_value) {
+            changed = true;
+        }
+
+        if ensure_field(development_entry, "directory", &directory_value) {
+            changed = true;
+        }
+        if ensure_field(
+            development_entry,
+            "active_project_directory",
+            &directory_value,
+        ) {
+            changed = true;
+        }
+
+        if ensure_field(development_entry, "restart_command", &restart_value) {
+            changed = true;
+        }
+        if ensure_field(
+            development_entry,
+            "active_project_restart_cmd",
+            &restart_value,
+        ) {
+            changed = true;
+        }
+
+        if ensure_field(development_entry, "log_command", &log_value) {
+            changed = true;
+        }
+        if ensure_field(development_entry, "active_project_log_cmd", &log_value) {
+            changed = true;
+        }
+    }
+
+    let mut final_name = name;
+    if final_name != name_value {
+        final_name = name_value.clone();
+        changed = true;
+    }
+
+    if changed {
+        updated_at = chrono::Utc::now().to_rfc3339();
+    }
+
+    (
+        Project {
+            id,
+            name: final_name,
+            description: normalized_description,
+            category_id,
+            is_active,
+            created_at,
+            updated_at,
+            category_values: Some(category_values),
+        },
+        changed,
+    )
+}
+
+pub fn create_project(project: Project) -> Result<(), String> {
+    info!("Creating new project: {}", project.name);
+    let mut data = load_project_data()?;
+    data.projects.push(project);
+    save_project_data(&data)?;
+    update_project_selector()?;
+    Ok(())
+}
+
+pub fn update_project(id: String, updates: Value) -> Result<(), String> {
+    let mut data = load_project_data()?;
+
+    if let Some(project) = data.projects.iter_mut().find(|p| p.id == id) {
+        // Update fields from the updates JSON value
+        if let Some(obj) = updates.as_object() {
+            if let Some(name) = obj.get("name").and_then(|v| v.as_str()) {
+                project.name = name.to_string();
+            }
+            if let Some(description) = obj.get("description") {
+                if description.is_null() {
+                    project.description = None;
+                } else if let Some(desc_str) = description.as_str() {
+                    project.description = Some(desc_str.to_string());
+                }
+            }
+            if let Some(cat_id) = obj.get("categoryId").and_then(|v| v.as_str()) {
+                project.category_id = cat_id.to_string();
+            }
+            // Handle category values
+            if let Some(cat_vals) = obj.get("categoryValues").and_then(|v| v.as_object()) {
+                let mut cat_vals_map = std::collections::HashMap::new();
+                for (cat_id, cat_data) in cat_vals {
+                    if let Some(cat_obj) = cat_data.as_object() {
+                        let mut var_vals_map = std::collections::HashMap::new();
+                        for (var_id, value) in cat_obj {
+                            if let Some(val_str) = value.as_str() {
+                                var_vals_map.insert(var_id.clone(), val_str.to_string());
+                            }
+                        }
+                        cat_vals_map.insert(cat_id.clone(), var_vals_map);
+                    }
+                }
+                project.category_values = Some(cat_vals_map);
+            }
+            // Update timestamp
+            project.updated_at = chrono::Utc::now().to_rfc3339();
+        }
+        save_project_data(&data)
+    } else {
+        Err("Project not found".to_string())
+    }
+}
+
+pub fn delete_project(id: String) -> Result<(), String> {
+    let mut data = load_project_data()?;
+    data.projects.retain(|p| p.id != id);
+
+    // Clear active project if it was deleted
+    if data.active_project_id == Some(id.clone()) {
+        data.active_project_id = None;
+    }
+
+    save_project_data(&data)?;
+    update_project_selector()?;
+    Ok(())
+}
+
+pub fn set_active_project(id: Option<String>) -> Result<(), String> {
+    info!("Setting active project: {:?}", id);
+    let mut data = load_project_data()?;
+
+    // Verify project exists if id is provided
+    if let Some(ref project_id) = id {
+        if !data.projects.iter().any(|p| p.id == *project_id) {
+            return Err("Project not found".to_string());
+        }
+    }
+
+    data.active_project_id = id;
+    save_project_data(&data)?;
+
+    // Update Espanso config for active project
+    if let Some(active_id) = &data.active_project_id {
+        if let Some(project) = data.projects.iter().find(|p| p.id == *active_id) {
+            update_espanso_project_vars(project)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// `(variable name, value)` pairs resolved from a project's category values
+pub type ProjectVariableValues = Vec<(String, String)>;
+
+/// Resolve a project's category values to `(variable name, value)` pairs
+///
+/// Only variables defined by the project's categories and set to a non-blank value
+/// are included.
+pub fn project_variable_values(project: &Project) -> ProjectVariableValues {
+    let mut values = vec![];
+
+    let Some(category_values) = &project.category_values else {
+        return values;
+    };
+    let Ok(categories_data) = load_project_categories_data() else {
+        return values;
+    };
+
+    for (category_id, variable_values) in category_values {
+        let Some(category) = categories_data
+            .categories
+            .iter()
+            .find(|c| c.id == *category_id)
+        else {
+            continue;
+        };
+
+        for variable_def in &category.variable_definitions {
+            if let Some(value) = variable_values.get(&variable_def.id) {
+                if !value.trim().is_empty() {
+                    values.push((variable_def.name.clone(), value.clone()));
+                }
+            }
+        }
+    }
+
+    values
+}
+
+/// Get the active project, if any, with its resolved variable values
+pub fn active_project_variables() -> Result<Option<(Project, ProjectVariableValues)>, String> {
+    let data = load_project_data()?;
+    let Some(active_id) = data.active_project_id else {
+        return Ok(None);
+    };
+
+    Ok(data
+        .projects
+        .into_iter()
+        .find(|p| p.id == active_id)
+        .map(|project| {
+            let values = project_variable_values(&project);
+            (project, values)
+        }))
+}
+
+pub fn update_espanso_project_vars(project: &Project) -> Result<(), String> {
+    use crate::paths::get_espanso_file_path;
+    let espanso_path = get_espanso_file_path("project_active_vars.yml")?;
+
+    let mut yaml_content = format!(
+        r#"# Generated active project variables for: {}
+global_vars:"#,
+        escape_yaml_value(&project.name)
+    );
+
+    for (name, value) in project_variable_values(project) {
+        let escaped_value = escape_yaml_value(&value);
+        // Check if the value is a multi-line string starting with |
+        if escaped_value.starts_with('|') {
+            // For multi-line strings, we need to adjust the indentation
+            // The escape_yaml_value already adds 2 spaces, but we need 8 total for proper YAML structure
+            let lines: Vec<&str> = escaped_value.lines().collect();
+            yaml_content.push_str(&format!(
+                r#"
+  - name: {}
+    type: echo
+    params:
+      echo: {}"#,
+                name,
+                lines[0] // This is the "|" character
+            ));
+            // Add the remaining lines with proper indentation (8 spaces total)
+            for line in lines.iter().skip(1) {
+                yaml_content.push_str(&format!("\n      {}", line));
+            }
+        } else {
+            // For single-line strings, use the original format
+            yaml_content.push_str(&format!(
+                r#"
+  - name: {}
+    type: echo
+    params:
+      echo: {}"#,
+                name, escaped_value
+            ));
+        }
+    }
+
+    yaml_content.push('\n');
+
+    // Validate YAML before writing
+    serde_yaml::from_str::<serde_yaml::Value>(&yaml_content)
+        .map_err(|e| format!("Generated invalid YAML: {}", e))?;
+
+    // Use atomic write for safety
+    atomic_write(&espanso_path, &yaml_content)
+        .map_err(|e| format!("Failed to write Espanso project vars: {}", e))?;
+
+    // Also update the project selector
+    update_project_selector()?;
+
+    Ok(())
+}
+
+/// CLI subcommand the generated `:project` selector calls to switch projects
+pub const SELECT_PROJECT_SUBCOMMAND: &str = "select-project";
+
+pub fn update_project_selector() -> Result<(), String> {
+    let data = load_project_data()?;
+    use crate::paths::get_espanso_file_path;
+    let selector_path = get_espanso_file_path("project_selector.yml")?;
+
+    // The selector runs this executable so picking a project really activates it
+    let executable = std::env::current_exe()
+        .map_err(|e| format!("Failed to locate application executable: {}", e))?;
+
+    // Build the choices for the selector
+    let mut choices = vec![];
+    for project in &data.projects {
+        choices.push(format!(
+            r#"          - label: {}
+            id: "{}""#,
+            escape_yaml_value(&project.name),
+            project.id
+        ));
+    }
+
+    let yaml_content = format!(
+        r#"# Generated project selector for quick switching
+matches:
+  - trigger: ":project"
+    replace: "{{{{switch_project}}}}"
+    vars:
+      - name: project_choice
+        type: choice
+        params:
+          values:
+{}
+      - name: switch_project
+        type: script
+        params:
+          args:
+            - {}
+            - {}
+            - "{{{{project_choice}}}}"
+"#,
+        choices.join("\n"),
+        escape_yaml_value(&executable.display().to_string()),
+        SELECT_PROJECT_SUBCOMMAND
+    );
+
+    atomic_write(&selector_path, &yaml_content)
+        .map_err(|e| format!("Failed to write project selector: {}", e))?;
+
+    Ok(())
+}
+
+pub fn clear_project_espanso_config() -> Result<(), String> {
+    use crate::paths::get_espanso_file_path;
+    let espanso_path = get_espanso_file_path("project_active_vars.yml")?;
+
+    // Write an empty config file with a comment
+    let empty_content =
+        "# No active project - project variables will not be available\nglobal_vars: []\n";
+
+    atomic_write(&espanso_path, empty_content)
+        .map_err(|e| format!("Failed to clear project config: {}", e))?;
+
+    info!("Cleared project Espanso config");
+    Ok(())
+}
@@ -0,0 +1,714 @@
+//! Render Espanso replacements outside the GUI
+//!
+//! Mirrors what Espanso does on expansion as closely as is possible without a
+//! keyboard or clipboard: `{{var}}` references are resolved against the match's own
+//! vars, then global vars, then the active project's and custom variables, with
+//! vars that reference other vars resolved recursively. `shell` and `script` vars
+//! are left unresolved.
+
+use chrono::{DateTime, Duration, Local, Months};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::fs;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::espanso::Replacement;
+use crate::paths::get_espanso_match_dir_internal;
+use crate::projects::active_project_variables;
+use crate::variables::load_custom_variables_data;
+
+const CURSOR_MARKER: &str = "$|$";
+const DEFAULT_DATE_FORMAT: &str = "%Y-%m-%d";
+const DEFAULT_TIME_FORMAT: &str = "%H:%M:%S";
+const DEFAULT_DATETIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// Everything a replacement can draw variable values from
+#[derive(Debug, Clone, Default)]
+pub struct RenderContext {
+    /// `global_vars` entries from the match files, as Espanso var definitions
+    pub global_vars: Vec<Value>,
+    pub project_vars: HashMap<String, String>,
+    pub custom_vars: HashMap<String, String>,
+    /// Values for `{{form.field}}` (or `{{form_var.field}}`) references, keyed by field
+    pub form_values: HashMap<String, String>,
+    /// Chosen value (label or id) for `choice` vars and `random` vars, keyed by var name
+    pub choices: HashMap<String, String>,
+    /// Values that take precedence over every other source, keyed by var name
+    pub overrides: HashMap<String, String>,
+    pub clipboard: Option<String>,
+    /// Time used for date vars; the current time when unset
+    pub now: Option<DateTime<Local>>,
+}
+
+/// How a single variable was resolved
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RenderTrace {
+    pub name: String,
+    /// override, local, global, project, custom, form, builtin or unresolved
+    pub source: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub var_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RenderResult {
+    pub text: String,
+    /// Character offset of the `$|$` cursor marker, which is removed from `text`
+    pub cursor_position: Option<usize>,
+    pub trace: Vec<RenderTrace>,
+    pub unresolved: Vec<String>,
+}
+
+/// A `{{name:parameter|filter|filter}}` reference
+struct VarRef<'a> {
+    name: &'a str,
+    parameter: Option<&'a str>,
+    filters: Vec<&'a str>,
+}
+
+fn parse_var_ref(inner: &str) -> VarRef<'_> {
+    let mut parts = inner.split('|');
+    let head = parts.next().unwrap_or_default();
+    let (name, parameter) = match head.split_once(':') {
+        Some((name, parameter)) => (name.trim(), Some(parameter.trim())),
+        None => (head.trim(), None),
+    };
+
+    VarRef {
+        name,
+        parameter,
+        filters: parts.map(|f| f.trim()).collect(),
+    }
+}
+
+// ========== Dates ==========
+
+/// Apply an offset such as `+2d`, `-1w` or `+3M` (s, m, h, d, w, M, y)
+fn apply_date_offset(date: DateTime<Local>, offset: &str) -> Option<DateTime<Local>> {
+    let unit = offset.chars().last()?;
+    let value: i64 = offset[..offset.len() - unit.len_utf8()].parse().ok()?;
+
+    match unit {
+        's' => date.checked_add_signed(Duration::seconds(value)),
+        'm' => date.checked_add_signed(Duration::minutes(value)),
+        'h' => date.checked_add_signed(Duration::hours(value)),
+        'd' => date.checked_add_signed(Duration::days(value)),
+        'w' => date.checked_add_signed(Duration::weeks(value)),
+        'M' | 'y' => {
+            let months = if unit == 'y' { value * 12 } else { value };
+            let delta = Months::new(u32::try_from(months.unsigned_abs()).ok()?);
+            if months >= 0 {
+                date.checked_add_months(delta)
+            } else {
+                date.checked_sub_months(delta)
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Format a date with a strftime pattern, rejecting invalid patterns instead of panicking
+fn format_date(date: DateTime<Local>, format: &str) -> Result<String, String> {
+    let mut formatted = String::new();
+    write!(formatted, "{}", date.format(format))
+        .map_err(|_| format!("Invalid date format: {}", format))?;
+    Ok(formatted)
+}
+
+// ========== Filters ==========
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first
+            .to_uppercase()
+            .chain(chars.flat_map(|c| c.to_lowercase()))
+            .collect(),
+        None => String::new(),
+    }
+}
+
+/// Apply preview filters (`upper`, `trim`, `snake`, `slice:0:5`, ...) in order
+fn apply_filters(value: String, filters: &[&str]) -> String {
+    filters.iter().fold(value, |value, filter| {
+        match filter.to_lowercase().as_str() {
+            "lower" | "lowercase" => value.to_lowercase(),
+            "upper" | "uppercase" => value.to_uppercase(),
+            "trim" => value.trim().to_string(),
+            "capitalize" => capitalize(&value),
+            "title" => value
+                .split(' ')
+                .map(capitalize)
+                .collect::<Vec<_>>()
+                .join(" "),
+            "snake" => value
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join("_")
+                .to_lowercase(),
+            "kebab" => value
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join("-")
+                .to_lowercase(),
+            "camel" | "pascal" => value
+                .split_whitespace()
+                .enumerate()
+                .map(|(i, word)| {
+                    if i == 0 && filter.eq_ignore_ascii_case("camel") {
+                        word.to_lowercase()
+                    } else {
+                        capitalize(word)
+                    }
+                })
+                .collect(),
+            "reverse" => value.chars().rev().collect(),
+            "length" => value.chars().count().to_string(),
+            _ => {
+                let params: Vec<&str> = filter.split(':').collect();
+                match params.as_slice() {
+                    ["slice", start, rest @ ..] => {
+                        let chars: Vec<char> = value.chars().collect();
+                        let start = start.parse::<usize>().unwrap_or(0).min(chars.len());
+                        let end = rest
+                            .first()
+                            .and_then(|e| e.parse::<usize>().ok())
+                            .unwrap_or(chars.len())
+                            .clamp(start, chars.len());
+                        chars[start..end].iter().collect()
+                    }
+                    ["replace", search, replacement, ..] => value.replace(search, replacement),
+                    _ => value,
+                }
+            }
+        }
+    })
+}
+
+// ========== Rendering ==========
+
+/// Pick a pseudo-random index; good enough for previews
+fn random_index(len: usize) -> usize {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.subsec_nanos())
+        .unwrap_or_default();
+    nanos as usize % len.max(1)
+}
+
+fn find_var<'v>(vars: &'v [Value], name: &str) -> Option<&'v Value> {
+    vars.iter()
+        .find(|var| var.get("name").and_then(|n| n.as_str()) == Some(name))
+}
+
+fn string_param<'v>(var: &'v Value, param: &str) -> Option<&'v str> {
+    var.get("params")
+        .and_then(|p| p.get(param))
+        .and_then(|v| v.as_str())
+}
+
+struct Renderer<'a> {
+    ctx: &'a RenderContext,
+    local_vars: &'a [Value],
+    now: DateTime<Local>,
+    resolved: HashMap<String, Option<String>>,
+    stack: Vec<String>,
+    trace: Vec<RenderTrace>,
+    unresolved: Vec<String>,
+}
+
+impl Renderer<'_> {
+    fn record(
+        &mut self,
+        name: &str,
+        source: &str,
+        var_type: Option<&str>,
+        value: &Option<String>,
+        note: Option<String>,
+    ) {
+        if value.is_none() && !self.unresolved.iter().any(|n| n == name) {
+            self.unresolved.push(name.to_string());
+        }
+        if self.trace.iter().any(|t| t.name == name) {
+            return;
+        }
+        self.trace.push(RenderTrace {
+            name: name.to_string(),
+            source: source.to_string(),
+            var_type: var_type.map(|t| t.to_string()),
+            value: value.clone(),
+            note,
+        });
+    }
+
+    /// Render every `{{...}}` reference in `text`; `\{{` produces a literal `{{`
+    fn render_text(&mut self, text: &str) -> Result<String, String> {
+        let mut output = String::new();
+        let mut rest = text;
+
+        while let Some(start) = rest.find("{{") {
+            if rest[..start].ends_with('\\') {
+                output.push_str(&rest[..start - 1]);
+                output.push_str("{{");
+                rest = &rest[start + 2..];
+                continue;
+            }
+
+            let after = &rest[start + 2..];
+            let Some(end) = after.find("}}") else {
+                break;
+            };
+
+            output.push_str(&rest[..start]);
+            let var_ref = parse_var_ref(&after[..end]);
+            match self.resolve(&var_ref)? {
+                Some(value) => output.push_str(&apply_filters(value, &var_ref.filters)),
+                None => {
+                    output.push('[');
+                    output.push_str(var_ref.name);
+                    output.push(']');
+                }
+            }
+
+            rest = &after[end + 2..];
+        }
+
+        output.push_str(rest);
+        Ok(output)
+    }
+
+    fn resolve(&mut self, var_ref: &VarRef) -> Result<Option<String>, String> {
+        let name = var_ref.name;
+        let key = match var_ref.parameter {
+            Some(parameter) => format!("{}:{}", name, parameter),
+            None => name.to_string(),
+        };
+        if let Some(value) = self.resolved.get(&key) {
+            return Ok(value.clone());
+        }
+
+        if self.stack.iter().any(|n| n == name) {
+            return Err(format!(
+                "Circular variable reference: {} -> {}",
+                self.stack.join(" -> "),
+                name
+            ));
+        }
+
+        self.stack.push(name.to_string());
+        let value = self.resolve_uncached(var_ref);
+        self.stack.pop();
+
+        let value = value?;
+        self.resolved.insert(key, value.clone());
+        Ok(value)
+    }
+
+    fn resolve_uncached(&mut self, var_ref: &VarRef) -> Result<Option<String>, String> {
+        let name = var_ref.name;
+        let ctx = self.ctx;
+
+        if let Some(value) = ctx.overrides.get(name) {
+            let value = Some(value.clone());
+            self.record(name, "override", None, &value, None);
+            return Ok(value);
+        }
+
+        if let Some(var) = find_var(self.local_vars, name) {
+            return self.evaluate_var(name, var, var_ref.parameter, "local");
+        }
+        if let Some(var) = find_var(&ctx.global_vars, name) {
+            return self.evaluate_var(name, var, var_ref.parameter, "global");
+        }
+
+        if let Some((_, field)) = name.split_once('.') {
+            let value = ctx
+                .form_values
+                .get(name)
+                .or_else(|| ctx.form_values.get(field))
+                .cloned();
+            let note = value.is_none().then(|| "No form value given".to_string());
+            self.record(name, "form", Some("form"), &value, note);
+            return Ok(value);
+        }
+
+        if let Some(value) = ctx.project_vars.get(name) {
+            let value = Some(value.clone());
+            self.record(name, "project", None, &value, None);
+            return Ok(value);
+        }
+        if let Some(value) = ctx.custom_vars.get(name) {
+            let value = Some(value.clone());
+            self.record(name, "custom", None, &value, None);
+            return Ok(value);
+        }
+
+        let value = self.builtin(name, var_ref.parameter)?;
+        if value.is_some() {
+            self.record(name, "builtin", None, &value, None);
+        } else {
+            self.record(name, "unresolved", None, &value, None);
+        }
+        Ok(value)
+    }
+
+    /// Evaluate a declared Espanso var
+    fn evaluate_var(
+        &mut self,
+        name: &str,
+        var: &Value,
+        parameter: Option<&str>,
+        source: &str,
+    ) -> Result<Option<String>, String> {
+        let var_type = var.get("type").and_then(|t| t.as_str()).unwrap_or_default();
+        let mut note = None;
+
+        let value = match var_type {
+            "echo" => match string_param(var, "echo") {
+                Some(echo) => Some(self.render_text(echo)?),
+                None => None,
+            },
+            "date" => {
+                let mut date = self.now;
+                if let Some(offset) = var
+                    .get("params")
+                    .and_then(|p| p.get("offset"))
+                    .and_then(|o| o.as_i64())
+                {
+                    date = date
+                        .checked_add_signed(Duration::seconds(offset))
+                        .ok_or_else(|| format!("{}: date offset out of range", name))?;
+                }
+                if let Some(parameter) = parameter {
+                    date = apply_date_offset(date, parameter)
+                        .ok_or_else(|| format!("{}: invalid date offset '{}'", name, parameter))?;
+                }
+                let format = string_param(var, "format").unwrap_or(DEFAULT_DATE_FORMAT);
+                Some(format_date(date, format)?)
+            }
+            "random" => {
+                let choices: Vec<&str> = var
+                    .get("params")
+                    .and_then(|p| p.get("choices"))
+                    .and_then(|c| c.as_array())
+                    .map(|c| c.iter().filter_map(|v| v.as_str()).collect())
+                    .unwrap_or_default();
+                let chosen = match self.ctx.choices.get(name) {
+                    Some(chosen) => Some(chosen.as_str()),
+                    None => choices.get(random_index(choices.len())).copied(),
+                };
+                match chosen {
+                    Some(chosen) => Some(self.render_text(chosen)?),
+                    None => None,
+                }
+            }
+            "choice" => {
+                let values: Vec<(String, String)> = var
+                    .get("params")
+                    .and_then(|p| p.get("values"))
+                    .and_then(|v| v.as_array())
+                    .map(|values| {
+                        values
+                            .iter()
+                            .filter_map(|v| match v {
+                                Value::String(s) => Some((s.clone(), s.clone())),
+                                _ => {
+                                    let label = v.get("label").and_then(|l| l.as_str())?;
+                                    let id = v.get("id").and_then(|i| i.as_str()).unwrap_or(label);
+                                    Some((label.to_string(), id.to_string()))
+                                }
+                            })
+                            .collect()
+                    })
+                    .unwrap_or_default();
+
+                // Espanso inserts the id of the chosen entry
+                match self.ctx.choices.get(name) {
+                    Some(chosen) => Some(
+                        values
+                            .iter()
+                            .find(|(label, id)| label == chosen || id == chosen)
+                            .map(|(_, id)| id.clone())
+                            .unwrap_or_else(|| chosen.clone()),
+                    ),
+                    None => {
+                        note = Some("No choice given, using the first value".to_string());
+                        values.first().map(|(_, id)| id.clone())
+                    }
+                }
+            }
+            "clipboard" => {
+                if self.ctx.clipboard.is_none() {
+                    note = Some("No clipboard content given".to_string());
+                }
+                self.ctx.clipboard.clone()
+            }
+            "shell" | "script" => {
+                note = Some(format!("{} vars are not executed when rendering", var_type));
+                None
+            }
+            "form" => {
+                note = Some("Reference form fields as {{name.field}}".to_string());
+                None
+            }
+            _ => {
+                note = Some(format!("Unsupported var type '{}'", var_type));
+                None
+            }
+        };
+
+        self.record(name, source, Some(var_type), &value, note);
+        Ok(value)
+    }
+
+    /// Built-in variables the app's preview supports without a declaration
+    fn builtin(&mut self, name: &str, parameter: Option<&str>) -> Result<Option<String>, String> {
+        let value = match name {
+            "date" | "time" | "datetime" => {
+                let default_format = match name {
+                    "date" => DEFAULT_DATE_FORMAT,
+                    "time" => DEFAULT_TIME_FORMAT,
+                    _ => DEFAULT_DATETIME_FORMAT,
+                };
+                match parameter {
+                    Some(parameter) if parameter.contains('%') => format_date(self.now, parameter)?,
+                    Some(parameter) => {
+                        let date = apply_date_offset(self.now, parameter).ok_or_else(|| {
+                            format!("{}: invalid date offset '{}'", name, parameter)
+                        })?;
+                        format_date(date, default_format)?
+                    }
+                    None => format_date(self.now, default_format)?,
+                }
+            }
+            "year" => format_date(self.now, "%Y")?,
+            "month" => format_date(self.now, "%m")?,
+            "day" => format_date(self.now, "%d")?,
+            "uuid" => uuid::Uuid::new_v4().to_string(),
+            "clipboard" => match &self.ctx.clipboard {
+                Some(clipboard) => clipboard.clone(),
+                None => return Ok(None),
+            },
+            _ => return Ok(None),
+        };
+
+        Ok(Some(value))
+    }
+}
+
+/// Render a replacement, returning the expanded text and how each variable resolved
+pub fn render_replacement(
+    replacement: &Replacement,
+    ctx: &RenderContext,
+) -> Result<RenderResult, String> {
+    let local_vars = replacement
+        .vars
+        .as_ref()
+        .and_then(|v| v.as_array())
+        .map(|v| v.as_slice())
+        .unwrap_or_default();
+
+    let mut renderer = Renderer {
+        ctx,
+        local_vars,
+        now: ctx.now.unwrap_or_else(Local::now),
+        resolved: HashMap::new(),
+        stack: vec![],
+        trace: vec![],
+        unresolved: vec![],
+    };
+
+    let template = replacement.replace.replace("\\n", "\n");
+    let mut text = renderer.render_text(&template)?;
+
+    let cursor_position = text.find(CURSOR_MARKER).map(|index| {
+        let position = text[..index].chars().count();
+        text.replace_range(index..index + CURSOR_MARKER.len(), "");
+        position
+    });
+
+    Ok(RenderResult {
+        text,
+        cursor_position,
+        trace: renderer.trace,
+        unresolved: renderer.unresolved,
+    })
+}
+
+/// Build a context from the match directory's global vars, the active project
+/// and the custom variables
+///
+/// `project_active_vars.yml` is skipped because the active project's variables are
+/// read directly, and files prefixed with `_` are skipped because Espanso only
+/// loads them through `imports`.
+pub fn load_render_context() -> Result<RenderContext, String> {
+    let mut ctx = RenderContext::default();
+
+    let match_dir = get_espanso_match_dir_internal()?;
+    if let Ok(entries) = fs::read_dir(&match_dir) {
+        let mut paths: Vec<_> = entries.flatten().map(|e| e.path()).collect();
+        paths.sort();
+
+        for path in paths {
+            let file_name = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or_default();
+            let is_yaml = file_name.ends_with(".yml") || file_name.ends_with(".yaml");
+            if !is_yaml || file_name.starts_with('_') || file_name == "project_active_vars.yml" {
+                continue;
+            }
+
+            let Ok(contents) = fs::read_to_string(&path) else {
+                continue;
+            };
+            if let Ok(config) = serde_yaml::from_str::<Value>(&contents) {
+                if let Some(vars) = config.get("global_vars").and_then(|v| v.as_array()) {
+                    ctx.global_vars.extend(vars.iter().cloned());
+                }
+            }
+        }
+    }
+
+    if let Some((_, values)) = active_project_variables()? {
+        ctx.project_vars.extend(values);
+    }
+
+    for category in load_custom_variables_data()?.categories {
+        for variable in category.variables {
+            ctx.custom_vars.insert(variable.name, variable.value);
+        }
+    }
+
+    Ok(ctx)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use serde_json::json;
+
+    fn replacement(replace: &str, vars: Value) -> Replacement {
+        Replacement {
+            trigger: ":test".to_string(),
+            replace: replace.to_string(),
+            source: "test.yml".to_string(),
+            vars: Some(vars),
+            metadata: HashMap::new(),
+        }
+    }
+
+    fn context() -> RenderContext {
+        RenderContext {
+            now: Some(Local.with_ymd_and_hms(2024, 1, 31, 9, 30, 0).unwrap()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_render_nested_vars_and_sources() {
+        let mut ctx = context();
+        ctx.global_vars = vec![
+            json!({ "name": "greeting", "type": "echo", "params": { "echo": "Hello {{user}}" } }),
+        ];
+        ctx.custom_vars
+            .insert("user".to_string(), "ada".to_string());
+        ctx.project_vars
+            .insert("directory".to_string(), "/src/app".to_string());
+
+        let result = render_replacement(
+            &replacement(
+                "{{greeting|upper}} in {{directory}}$|$ {{missing}}",
+                json!([]),
+            ),
+            &ctx,
+        )
+        .unwrap();
+
+        assert_eq!(result.text, "HELLO ADA in /src/app [missing]");
+        assert_eq!(result.cursor_position, Some(21));
+        assert_eq!(result.unresolved, vec!["missing"]);
+
+        let sources: Vec<(&str, &str)> = result
+            .trace
+            .iter()
+            .map(|t| (t.name.as_str(), t.source.as_str()))
+            .collect();
+        assert_eq!(
+            sources,
+            vec![
+                ("user", "custom"),
+                ("greeting", "global"),
+                ("directory", "project"),
+                ("missing", "unresolved"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_render_dates_choices_and_forms() {
+        let mut ctx = context();
+        ctx.choices
+            .insert("env".to_string(), "Production".to_string());
+        ctx.form_values
+            .insert("name".to_string(), "Grace".to_string());
+
+        let vars = json!([
+            { "name": "tomorrow", "type": "date", "params": { "format": "%d/%m", "offset": 86400 } },
+            { "name": "env", "type": "choice", "params": { "values": [
+                { "label": "Staging", "id": "stg" },
+                { "label": "Production", "id": "prd" }
+            ] } },
+            { "name": "out", "type": "shell", "params": { "cmd": "echo hi" } }
+        ]);
+        let result = render_replacement(
+            &replacement(
+                "{{tomorrow}} {{date:+1M}} {{env}} {{form.name}} {{out}} \\{{literal}}\\n",
+                vars,
+            ),
+            &ctx,
+        )
+        .unwrap();
+
+        assert_eq!(
+            result.text,
+            "01/02 2024-02-29 prd Grace [out] {{literal}}\n"
+        );
+        assert_eq!(result.unresolved, vec!["out"]);
+    }
+
+    #[test]
+    fn test_render_detects_cycles() {
+        let vars = json!([
+            { "name": "a", "type": "echo", "params": { "echo": "{{b}}" } },
+            { "name": "b", "type": "echo", "params": { "echo": "{{a}}" } }
+        ]);
+        let err = render_replacement(&replacement("{{a}}", vars), &context()).unwrap_err();
+        assert!(err.contains("a -> b -> a"));
+    }
+
+    #[test]
+    fn test_apply_filters() {
+        assert_eq!(
+            apply_filters("hello big world".to_string(), &["camel"]),
+            "helloBigWorld"
+        );
+        assert_eq!(
+            apply_filters("hello big world".to_string(), &["snake"]),
+            "hello_big_world"
+        );
+        assert_eq!(
+            apply_filters(" abcdef ".to_string(), &["trim", "slice:1:3"]),
+            "bc"
+        );
+    }
+}
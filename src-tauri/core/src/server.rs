@@ -0,0 +1,473 @@
+//! Opt-in localhost API for editor and launcher integrations
+//!
+//! `brm serve` binds to 127.0.0.1 only and requires the token stored in the app
+//! data directory on every request, either as `Authorization: Bearer <token>` or
+//! as a `?token=` query parameter (for `EventSource`, which cannot set headers).
+//!
+//! - `POST /rpc` takes JSON-RPC 2.0 requests (single or batch)
+//! - `GET /events` streams Server-Sent Events when projects, custom variables,
+//!   saved extensions or match files change on disk
+
+use log::{error, info, warn};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, SystemTime};
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use crate::espanso::{list_espanso_yaml_files, read_espanso_file};
+use crate::extensions::load_saved_extensions_data;
+use crate::paths::{
+    get_app_data_file_path, get_espanso_config_dir_internal, get_espanso_file_path,
+};
+use crate::projects::{load_project_data, set_active_project};
+use crate::variables::load_custom_variables_data;
+
+pub const DEFAULT_PORT: u16 = 47_321;
+
+const TOKEN_FILE: &str = "api_token";
+const WATCH_INTERVAL: Duration = Duration::from_secs(1);
+const KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(15);
+
+// JSON-RPC 2.0 error codes
+pub(crate) const PARSE_ERROR: i64 = -32700;
+pub(crate) const INVALID_REQUEST: i64 = -32600;
+pub(crate) const METHOD_NOT_FOUND: i64 = -32601;
+pub(crate) const INVALID_PARAMS: i64 = -32602;
+pub(crate) const APP_ERROR: i64 = -32000;
+
+#[derive(Debug)]
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+}
+
+impl RpcError {
+    pub(crate) fn new(code: i64, message: impl Into<String>) -> Self {
+        RpcError {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+impl From<String> for RpcError {
+    fn from(message: String) -> Self {
+        RpcError::new(APP_ERROR, message)
+    }
+}
+
+// ========== Token ==========
+
+fn get_token_file_path() -> Result<PathBuf, String> {
+    get_app_data_file_path(TOKEN_FILE)
+}
+
+/// Load the API token, generating (or regenerating with `rotate`) it when needed
+pub fn load_or_create_token(rotate: bool) -> Result<String, String> {
+    let file_path = get_token_file_path()?;
+
+    if !rotate {
+        if let Ok(token) = fs::read_to_string(&file_path) {
+            let token = token.trim().to_string();
+            if !token.is_empty() {
+                return Ok(token);
+            }
+        }
+    }
+
+    if let Some(parent) = file_path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create app data directory: {}", e))?;
+    }
+
+    let token = format!(
+        "{}{}",
+        uuid::Uuid::new_v4().simple(),
+        uuid::Uuid::new_v4().simple()
+    );
+    fs::write(&file_path, &token).map_err(|e| format!("Failed to write API token: {}", e))?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&file_path, fs::Permissions::from_mode(0o600))
+            .map_err(|e| format!("Failed to restrict API token permissions: {}", e))?;
+    }
+
+    info!("Generated API token at {}", file_path.display());
+    Ok(token)
+}
+
+/// Compare tokens without short-circuiting on the first differing byte
+fn token_matches(expected: &str, provided: &str) -> bool {
+    expected.len() == provided.len()
+        && expected
+            .bytes()
+            .zip(provided.bytes())
+            .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+            == 0
+}
+
+/// Extract the token from the `Authorization` header or the `token` query parameter
+fn request_token(url: &str, authorization: Option<&str>) -> Option<String> {
+    if let Some(token) = authorization.and_then(|value| value.strip_prefix("Bearer ")) {
+        return Some(token.trim().to_string());
+    }
+
+    let (_, query) = url.split_once('?')?;
+    query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(key, _)| *key == "token")
+        .map(|(_, value)| value.to_string())
+}
+
+// ========== JSON-RPC ==========
+
+fn string_param(params: &Value, name: &str) -> Result<String, RpcError> {
+    params
+        .get(name)
+        .and_then(|v| v.as_str())
+        .map(|s| s.to_string())
+        .ok_or_else(|| RpcError::new(INVALID_PARAMS, format!("Missing string param '{}'", name)))
+}
+
+fn to_value<T: serde::Serialize>(value: T) -> Result<Value, RpcError> {
+    serde_json::to_value(value)
+        .map_err(|e| RpcError::new(APP_ERROR, format!("Failed to serialize result: {}", e)))
+}
+
+/// Run a single API method
+///
+/// Methods mirror the app's Tauri commands of the same purpose.
+pub fn dispatch(method: &str, params: &Value) -> Result<Value, RpcError> {
+    match method {
+        "projects.list" => to_value(load_project_data()?),
+        "projects.setActive" => {
+            let id = match params.get("projectId") {
+                None | Some(Value::Null) => None,
+                Some(Value::String(id)) => Some(id.clone()),
+                Some(_) => {
+                    return Err(RpcError::new(
+                        INVALID_PARAMS,
+                        "'projectId' must be a string or null",
+                    ))
+                }
+            };
+            set_active_project(id)?;
+            Ok(Value::Null)
+        }
+        "espanso.listFiles" => to_value(list_espanso_yaml_files()?),
+        "espanso.readFile" => {
+            let file = string_param(params, "file")?;
+            if file.contains('/') || file.contains('\\') || file.starts_with('.') {
+                return Err(RpcError::new(
+                    INVALID_PARAMS,
+                    "'file' must be a file name in the Espanso match directory",
+                ));
+            }
+            let path = get_espanso_file_path(&file)?;
+            to_value(read_espanso_file(path.display().to_string())?)
+        }
+        "variables.list" => to_value(load_custom_variables_data()?),
+        "extensions.list" => to_value(load_saved_extensions_data()?),
+        _ => Err(RpcError::new(
+            METHOD_NOT_FOUND,
+            format!("Method not found: {}", method),
+        )),
+    }
+}
+
+pub(crate) fn error_response(id: Value, error: RpcError) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": error.code, "message": error.message },
+    })
+}
+
+/// Handle one JSON-RPC request object, returning `None` for notifications
+fn handle_call(call: &Value) -> Option<Value> {
+    let id = call.get("id").cloned();
+    let method = call.get("method").and_then(|m| m.as_str());
+
+    let Some(method) = method.filter(|_| call.get("jsonrpc") == Some(&json!("2.0"))) else {
+        return Some(error_response(
+            id.unwrap_or(Value::Null),
+            RpcError::new(INVALID_REQUEST, "Invalid JSON-RPC 2.0 request"),
+        ));
+    };
+
+    let params = call.get("params").cloned().unwrap_or(Value::Null);
+    let result = dispatch(method, &params);
+
+    let id = id?;
+    Some(match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err(e) => error_response(id, e),
+    })
+}
+
+/// Handle a JSON-RPC request body, returning the response body if there is one
+pub fn handle_rpc(body: &str) -> Option<Value> {
+    let request: Value = match serde_json::from_str(body) {
+        Ok(request) => request,
+        Err(e) => {
+            return Some(error_response(
+                Value::Null,
+                RpcError::new(PARSE_ERROR, format!("Parse error: {}", e)),
+            ))
+        }
+    };
+
+    match request {
+        Value::Array(calls) if calls.is_empty() => Some(error_response(
+            Value::Null,
+            RpcError::new(INVALID_REQUEST, "Empty batch"),
+        )),
+        Value::Array(calls) => {
+            let responses: Vec<Value> = calls.iter().filter_map(handle_call).collect();
+            (!responses.is_empty()).then_some(Value::Array(responses))
+        }
+        call => handle_call(&call),
+    }
+}
+
+// ========== Change notifications ==========
+
+type Subscribers = Arc<Mutex<Vec<Sender<String>>>>;
+
+/// Files whose changes are pushed to `/events`, keyed by event name
+fn watched_sources() -> Result<Vec<(&'static str, PathBuf)>, String> {
+    let config_dir = get_espanso_config_dir_internal()?;
+    Ok(vec![
+        ("projects", config_dir.join("config").join("projects.json")),
+        (
+            "variables",
+            get_app_data_file_path("custom_variables.json")?,
+        ),
+        (
+            "extensions",
+            get_app_data_file_path("saved_extensions.json")?,
+        ),
+        ("matches", config_dir.join("match")),
+    ])
+}
+
+/// Latest modification time of a file, or of the entries of a directory
+fn last_modified(path: &PathBuf) -> Option<SystemTime> {
+    let metadata = fs::metadata(path).ok()?;
+    let mut latest = metadata.modified().ok()?;
+
+    if metadata.is_dir() {
+        for entry in fs::read_dir(path).ok()?.flatten() {
+            if let Ok(modified) = entry.metadata().and_then(|m| m.modified()) {
+                latest = latest.max(modified);
+            }
+        }
+    }
+
+    Some(latest)
+}
+
+/// Poll the watched files and broadcast an event to every subscriber on change
+fn watch_for_changes(subscribers: Subscribers) -> Result<(), String> {
+    let sources = watched_sources()?;
+
+    thread::spawn(move || {
+        let mut seen: HashMap<&str, Option<SystemTime>> = sources
+            .iter()
+            .map(|(event, path)| (*event, last_modified(path)))
+            .collect();
+
+        loop {
+            thread::sleep(WATCH_INTERVAL);
+
+            for (event, path) in &sources {
+                let modified = last_modified(path);
+                if seen.get(event) == Some(&modified) {
+                    continue;
+                }
+                seen.insert(event, modified);
+
+                let message = format!(
+                    "event: {}\ndata: {}\n\n",
+                    event,
+                    json!({ "changed": event, "path": path.display().to_string() })
+                );
+                let mut subscribers = subscribers.lock().unwrap_or_else(|e| e.into_inner());
+                subscribers.retain(|tx| tx.send(message.clone()).is_ok());
+            }
+        }
+    });
+
+    Ok(())
+}
+
+/// Stream events to a client until it disconnects
+fn stream_events(request: Request, events: Receiver<String>) {
+    let mut writer = request.into_writer();
+    let head = "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\nConnection: keep-alive\r\n\r\n: connected\n\n";
+    if writer
+        .write_all(head.as_bytes())
+        .and_then(|_| writer.flush())
+        .is_err()
+    {
+        return;
+    }
+
+    loop {
+        let chunk = match events.recv_timeout(KEEP_ALIVE_INTERVAL) {
+            Ok(message) => message,
+            Err(RecvTimeoutError::Timeout) => ": keep-alive\n\n".to_string(),
+            Err(RecvTimeoutError::Disconnected) => return,
+        };
+        if writer
+            .write_all(chunk.as_bytes())
+            .and_then(|_| writer.flush())
+            .is_err()
+        {
+            return;
+        }
+    }
+}
+
+// ========== Server ==========
+
+fn json_header() -> Header {
+    Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..])
+        .expect("static header is valid")
+}
+
+fn respond_json(request: Request, status: u16, body: &Value) {
+    let response = Response::from_string(body.to_string())
+        .with_status_code(status)
+        .with_header(json_header());
+    if let Err(e) = request.respond(response) {
+        warn!("Failed to send API response: {}", e);
+    }
+}
+
+fn handle_request(mut request: Request, token: &str, subscribers: &Subscribers) {
+    let authorization = request
+        .headers()
+        .iter()
+        .find(|h| h.field.equiv("Authorization"))
+        .map(|h| h.value.as_str().to_string());
+    let authorized = request_token(request.url(), authorization.as_deref())
+        .map(|provided| token_matches(token, &provided))
+        .unwrap_or(false);
+
+    if !authorized {
+        respond_json(
+            request,
+            401,
+            &json!({ "error": "Missing or invalid token" }),
+        );
+        return;
+    }
+
+    let path = request
+        .url()
+        .split('?')
+        .next()
+        .unwrap_or_default()
+        .to_string();
+    match (request.method(), path.as_str()) {
+        (Method::Post, "/rpc") => {
+            let mut body = String::new();
+            if let Err(e) = request.as_reader().read_to_string(&mut body) {
+                respond_json(
+                    request,
+                    400,
+                    &json!({ "error": format!("Failed to read body: {}", e) }),
+                );
+                return;
+            }
+            match handle_rpc(&body) {
+                Some(response) => respond_json(request, 200, &response),
+                None => {
+                    let _ = request.respond(Response::empty(204));
+                }
+            }
+        }
+        (Method::Get, "/events") => {
+            let (tx, rx) = channel();
+            subscribers
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .push(tx);
+            stream_events(request, rx);
+        }
+        _ => respond_json(request, 404, &json!({ "error": "Not found" })),
+    }
+}
+
+/// Serve the API on 127.0.0.1 until the process exits
+pub fn serve(port: u16, token: String) -> Result<(), String> {
+    let server = Server::http(("127.0.0.1", port))
+        .map_err(|e| format!("Failed to bind 127.0.0.1:{}: {}", port, e))?;
+    info!("Local API listening on http://127.0.0.1:{}", port);
+
+    let token = Arc::new(token);
+    let subscribers: Subscribers = Arc::new(Mutex::new(vec![]));
+    watch_for_changes(subscribers.clone())?;
+
+    for request in server.incoming_requests() {
+        let token = token.clone();
+        let subscribers = subscribers.clone();
+        thread::spawn(move || handle_request(request, &token, &subscribers));
+    }
+
+    error!("Local API server stopped");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_token() {
+        assert_eq!(
+            request_token("/rpc", Some("Bearer abc")),
+            Some("abc".to_string())
+        );
+        assert_eq!(
+            request_token("/events?x=1&token=abc", None),
+            Some("abc".to_string())
+        );
+        assert_eq!(request_token("/rpc", Some("Basic abc")), None);
+        assert!(token_matches("abc", "abc"));
+        assert!(!token_matches("abc", "abd"));
+        assert!(!token_matches("abc", "ab"));
+    }
+
+    #[test]
+    fn test_handle_rpc_errors() {
+        let response = handle_rpc("{not json").unwrap();
+        assert_eq!(response["error"]["code"], PARSE_ERROR);
+
+        let response = handle_rpc(r#"{"jsonrpc":"2.0","id":1,"method":"nope"}"#).unwrap();
+        assert_eq!(response["id"], 1);
+        assert_eq!(response["error"]["code"], METHOD_NOT_FOUND);
+
+        let response = handle_rpc(
+            r#"{"jsonrpc":"2.0","id":2,"method":"espanso.readFile","params":{"file":"../x.yml"}}"#,
+        )
+        .unwrap();
+        assert_eq!(response["error"]["code"], INVALID_PARAMS);
+
+        let response = handle_rpc(r#"[{"jsonrpc":"2.0","method":"nope"}]"#);
+        assert!(response.is_none());
+
+        let response = handle_rpc(r#"{"id":3,"method":"projects.list"}"#).unwrap();
+        assert_eq!(response["error"]["code"], INVALID_REQUEST);
+    }
+}
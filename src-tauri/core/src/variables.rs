@@ -0,0 +1,68 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CustomVariable {
+    pub id: String,
+    pub name: String,
+    pub value: String,
+    pub preview: Option<String>,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CustomVariableCategory {
+    pub id: String,
+    pub name: String,
+    pub icon: String,
+    pub variables: Vec<CustomVariable>,
+    pub color: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VariablesData {
+    pub categories: Vec<CustomVariableCategory>,
+    #[serde(rename = "lastUpdated")]
+    pub last_updated: String,
+}
+
+// Custom variables management functions
+fn get_custom_variables_file_path() -> Result<PathBuf, String> {
+    use crate::paths::get_app_data_file_path;
+    get_app_data_file_path("custom_variables.json")
+}
+
+pub fn load_custom_variables_data() -> Result<VariablesData, String> {
+    let file_path = get_custom_variables_file_path()?;
+
+    if file_path.exists() {
+        let content = fs::read_to_string(&file_path)
+            .map_err(|e| format!("Failed to read custom variables file: {}", e))?;
+        serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse custom variables data: {}", e))
+    } else {
+        // Return default empty data if file doesn't exist
+        Ok(VariablesData {
+            categories: vec![],
+            last_updated: chrono::Utc::now().to_rfc3339(),
+        })
+    }
+}
+
+pub fn save_custom_variables_data(data: &VariablesData) -> Result<(), String> {
+    let file_path = get_custom_variables_file_path()?;
+
+    // Ensure directory exists
+    if let Some(parent) = file_path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create custom variables directory: {}", e))?;
+    }
+
+    let json = serde_json::to_string_pretty(data)
+        .map_err(|e| format!("Failed to serialize custom variables data: {}", e))?;
+    fs::write(&file_path, json)
+        .map_err(|e| format!("Failed to write custom variables file: {}", e))?;
+
+    Ok(())
+}
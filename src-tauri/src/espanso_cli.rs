@@ -0,0 +1,26 @@
+use brm_core::espanso_cli::{
+    exec_trigger, list_live_matches, resolve_trigger, EspansoLiveMatch, EspansoMatchFilter,
+    TriggerResolution,
+};
+
+// ========== Tauri Commands ==========
+
+/// Tauri command: List the matches Espanso currently has loaded
+#[tauri::command]
+pub fn list_espanso_live_matches(
+    filter: Option<EspansoMatchFilter>,
+) -> Result<Vec<EspansoLiveMatch>, String> {
+    list_live_matches(&filter.unwrap_or_default())
+}
+
+/// Tauri command: Fire a trigger through `espanso match exec`
+#[tauri::command]
+pub fn exec_espanso_trigger(trigger: String) -> Result<(), String> {
+    exec_trigger(&trigger)
+}
+
+/// Tauri command: Check whether a trigger is live and where it is defined
+#[tauri::command]
+pub fn resolve_espanso_trigger(trigger: String) -> Result<TriggerResolution, String> {
+    resolve_trigger(&trigger)
+}
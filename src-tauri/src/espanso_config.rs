@@ -0,0 +1,39 @@
+use brm_core::espanso_config::{
+    delete_app_config, list_app_configs, read_app_config, validate_app_config, write_app_config,
+    EspansoAppConfig,
+};
+
+// ========== Tauri Commands ==========
+
+/// Tauri command: List default.yml and the app-specific configs in espanso/config
+#[tauri::command]
+pub fn list_espanso_app_configs() -> Result<Vec<String>, String> {
+    list_app_configs()
+}
+
+/// Tauri command: Read an Espanso config file
+#[tauri::command]
+pub fn read_espanso_app_config(file_name: String) -> Result<EspansoAppConfig, String> {
+    read_app_config(&file_name)
+}
+
+/// Tauri command: Validate and write an Espanso config file
+#[tauri::command]
+pub fn write_espanso_app_config(file_name: String, config: EspansoAppConfig) -> Result<(), String> {
+    write_app_config(&file_name, &config)
+}
+
+/// Tauri command: Delete an app-specific Espanso config file
+#[tauri::command]
+pub fn delete_espanso_app_config(file_name: String) -> Result<(), String> {
+    delete_app_config(&file_name)
+}
+
+/// Tauri command: Validate an Espanso config without writing it
+#[tauri::command]
+pub fn validate_espanso_app_config(
+    file_name: String,
+    config: EspansoAppConfig,
+) -> Result<Vec<String>, String> {
+    Ok(validate_app_config(&file_name, &config))
+}
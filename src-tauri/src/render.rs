@@ -0,0 +1,21 @@
+use brm_core::espanso::Replacement;
+use brm_core::render::{load_render_context, render_replacement, RenderResult};
+use std::collections::HashMap;
+
+// ========== Tauri Commands ==========
+
+/// Tauri command: Render a replacement with the global, project and custom variables
+///
+/// `form_values` and `choices` stand in for the answers Espanso would prompt for.
+#[tauri::command]
+pub fn render_espanso_replacement(
+    replacement: Replacement,
+    form_values: Option<HashMap<String, String>>,
+    choices: Option<HashMap<String, String>>,
+) -> Result<RenderResult, String> {
+    let mut ctx = load_render_context()?;
+    ctx.form_values = form_values.unwrap_or_default();
+    ctx.choices = choices.unwrap_or_default();
+
+    render_replacement(&replacement, &ctx)
+}